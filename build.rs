@@ -0,0 +1,241 @@
+//! Compiles the vendored LibOVR sources and tells cargo what to link.
+
+extern crate gcc;
+extern crate "pkg-config" as pkg_config;
+
+use std::os;
+use std::io::fs::PathExtensions;
+use std::io::process::Command;
+
+static LIBOVR_PATH: &'static str = "deps/oculus_sdk_linux/LibOVR";
+static THIRD_PARTY_PATH: &'static str = "deps/oculus_sdk_linux/3rdParty";
+
+/// Sources of libovr, as listed in deps/oculus_sdk_linux/LibOVR/Makefile.
+static LIBOVR_SOURCES: &'static [&'static str] = &[
+  "Src/Net/OVR_BitStream.cpp",
+  "Src/Net/OVR_Unix_Socket.cpp",
+  "Src/Net/OVR_NetworkPlugin.cpp",
+  "Src/Net/OVR_PacketizedTCPSocket.cpp",
+  "Src/Net/OVR_RPC1.cpp",
+  "Src/Net/OVR_Session.cpp",
+  "Src/Net/OVR_Socket.cpp",
+  "Src/Service/Service_NetClient.cpp",
+  "Src/Service/Service_NetSessionCommon.cpp",
+  "Src/Tracking/Tracking_SensorStateReader.cpp",
+  "Src/Displays/OVR_Display.cpp",
+  "Src/Displays/OVR_Linux_Display.cpp",
+  "Src/Displays/OVR_Linux_SDKWindow.cpp",
+  "Src/CAPI/CAPI_DistortionRenderer.cpp",
+  "Src/CAPI/CAPI_HSWDisplay.cpp",
+  "Src/CAPI/CAPI_FrameTimeManager.cpp",
+  "Src/CAPI/CAPI_HMDRenderState.cpp",
+  "Src/CAPI/CAPI_HMDState.cpp",
+  "Src/CAPI/CAPI_LatencyStatistics.cpp",
+  "Src/CAPI/GL/CAPI_GL_DistortionRenderer.cpp",
+  "Src/CAPI/GL/CAPI_GL_HSWDisplay.cpp",
+  "Src/CAPI/GL/CAPI_GL_Util.cpp",
+  "Src/CAPI/GL/CAPI_GLE.cpp",
+  "Src/Kernel/OVR_Alg.cpp",
+  "Src/Kernel/OVR_Allocator.cpp",
+  "Src/Kernel/OVR_Atomic.cpp",
+  "Src/Kernel/OVR_CRC32.cpp",
+  "Src/Kernel/OVR_File.cpp",
+  "Src/Kernel/OVR_FileFILE.cpp",
+  "Src/Kernel/OVR_Lockless.cpp",
+  "Src/Kernel/OVR_Log.cpp",
+  "Src/Kernel/OVR_Math.cpp",
+  "Src/Kernel/OVR_RefCount.cpp",
+  "Src/Kernel/OVR_SharedMemory.cpp",
+  "Src/Kernel/OVR_Std.cpp",
+  "Src/Kernel/OVR_String.cpp",
+  "Src/Kernel/OVR_String_FormatUtil.cpp",
+  "Src/Kernel/OVR_String_PathUtil.cpp",
+  "Src/Kernel/OVR_SysFile.cpp",
+  "Src/Kernel/OVR_System.cpp",
+  "Src/Kernel/OVR_ThreadsPthread.cpp",
+  "Src/Kernel/OVR_ThreadCommandQueue.cpp",
+  "Src/Kernel/OVR_Timer.cpp",
+  "Src/Kernel/OVR_UTF8Util.cpp",
+  "Src/Util/Util_Interface.cpp",
+  "Src/Util/Util_LatencyTest2Reader.cpp",
+  "Src/Util/Util_Render_Stereo.cpp",
+  "Src/Util/Util_SystemInfo.cpp",
+  "Src/OVR_CAPI.cpp",
+  "Src/OVR_SerialFormat.cpp",
+  "Src/OVR_JSON.cpp",
+  "Src/OVR_Profile.cpp",
+  "Src/OVR_Stereo.cpp",
+];
+
+static THIRD_PARTY_SOURCES: &'static [&'static str] = &[
+  "EDID/edid.cpp",
+  "TinyXml/tinyxml2.cpp",
+];
+
+/// System libraries libovr depends on. The C++ runtime is linked separately.
+static LINUX_LIBS: &'static [&'static str] = &["udev", "Xinerama", "Xrandr", "X11", "GL"];
+/// The pkg-config packages providing `LINUX_LIBS`.
+static LINUX_PACKAGES: &'static [&'static str] = &["libudev", "xinerama", "xrandr", "x11", "gl"];
+static MACOS_FRAMEWORKS: &'static [&'static str] = &["Cocoa", "IOKit", "CoreFoundation"];
+
+fn getenv(name: &str) -> String {
+  os::getenv(name).expect(format!("{} is not set", name).as_slice())
+}
+
+fn is_debug_profile() -> bool {
+  os::getenv("PROFILE").map_or(false, |p| p.as_slice() == "debug")
+}
+
+/// Links `name` with the given kind (`static`, `dylib` or `framework`).
+fn link(name: &str, kind: &str) {
+  println!("cargo:rustc-flags=-l {}:{}", name, kind);
+}
+
+fn link_search(dir: &str) {
+  println!("cargo:rustc-flags=-L {}", dir);
+}
+
+/// Links the system libraries a static libovr depends on, found through
+/// pkg-config where possible.
+fn link_linux_libs() {
+  for (package, lib) in LINUX_PACKAGES.iter().zip(LINUX_LIBS.iter()) {
+    if pkg_config::find_library(*package).is_err() {
+      link(*lib, "dylib");
+    }
+  }
+}
+
+/// Links a preinstalled libovr instead of the vendored sources, either from
+/// the directory in `LIBOVR_LIB_DIR` or through the `libovr` pkg-config
+/// package when the `system-libovr` feature is enabled. A static library is
+/// preferred if `LIBOVR_STATIC` is set or no shared library is present.
+/// Returns false if no preinstalled libovr was requested.
+fn link_system_libovr() -> bool {
+  let statik = os::getenv("LIBOVR_STATIC").is_some();
+
+  match os::getenv("LIBOVR_LIB_DIR") {
+    Some(dir) => {
+      let statik = statik || !Path::new(dir.as_slice()).join("libovr.so").exists();
+      link_search(dir.as_slice());
+      if statik {
+        link("ovr", "static");
+        link("stdc++", "dylib");
+        link_linux_libs();
+      } else {
+        link("ovr", "dylib");
+      }
+      return true;
+    }
+    None => {}
+  }
+
+  if os::getenv("CARGO_FEATURE_SYSTEM_LIBOVR").is_some() {
+    // With `statik` pkg-config also emits the private dependencies of libovr.
+    match pkg_config::find_library_opts("libovr", &pkg_config::Options{ statik: statik }) {
+      Ok(_)  => {
+        if statik {
+          link("stdc++", "dylib");
+        }
+      }
+      Err(e) => panic!("the system-libovr feature requires the libovr pkg-config package: {}", e),
+    }
+    return true;
+  }
+
+  false
+}
+
+/// Compiles the vendored sources into `libovr.a` in `OUT_DIR`. gcc picks the
+/// optimization level and debug info from the cargo profile and links the
+/// library; the defines are the ones of the SDK's debug build.
+fn build_libovr() {
+  let mut definitions = Vec::new();
+  if is_debug_profile() {
+    definitions.push(("DEBUG".to_string(), None));
+    definitions.push(("OVR_BUILD_DEBUG".to_string(), None));
+  }
+  let config = gcc::Config{
+    include_directories: vec![Path::new(LIBOVR_PATH),
+                              Path::new(format!("{}/Include", LIBOVR_PATH)),
+                              Path::new(format!("{}/Src", LIBOVR_PATH)),
+                              Path::new("deps/oculus_sdk_linux")],
+    definitions: definitions,
+    objects: Vec::new(),
+  };
+  let mut sources: Vec<String> = LIBOVR_SOURCES.iter().map(|src| format!("{}/{}", LIBOVR_PATH, src)).collect();
+  sources.extend(THIRD_PARTY_SOURCES.iter().map(|src| format!("{}/{}", THIRD_PARTY_PATH, src)));
+  let sources: Vec<&str> = sources.iter().map(|s| s.as_slice()).collect();
+  gcc::compile_library("libovr.a", &config, sources.as_slice());
+  link("stdc++", "dylib");
+}
+
+/// The layout shim used by tests/layout.rs, only built with the
+/// `layout-tests` feature. It is only linked by the test, so it is compiled
+/// directly instead of through gcc, which would link it into the library.
+fn build_layout_shim(out_dir: &str) {
+  if os::getenv("CARGO_FEATURE_LAYOUT_TESTS").is_none() {
+    return;
+  }
+  let cxx = os::getenv("CXX").unwrap_or("c++".to_string());
+  let object = format!("{}/ovr_layout.o", out_dir);
+  let status = Command::new(cxx.as_slice())
+    .arg("-c").arg("-fPIC")
+    .arg(format!("-I{}/Src", LIBOVR_PATH))
+    .arg("tests/ovr_layout.cpp")
+    .arg("-o").arg(object.as_slice())
+    .status()
+    .unwrap();
+  assert!(status.success(), "compiling tests/ovr_layout.cpp with {} failed", cxx);
+  let status = Command::new("ar")
+    .arg("crs").arg(format!("{}/libovr_layout.a", out_dir)).arg(object.as_slice())
+    .status()
+    .unwrap();
+  assert!(status.success(), "creating libovr_layout.a failed");
+  link_search(out_dir);
+}
+
+fn generate_bindings(out_dir: &str) {
+  if os::getenv("CARGO_FEATURE_GENERATE_BINDINGS").is_none() {
+    return;
+  }
+  let bindgen = os::getenv("BINDGEN").unwrap_or("bindgen".to_string());
+  let status = Command::new(bindgen.as_slice())
+    .arg("-match").arg("OVR_CAPI")
+    .arg("-o").arg(format!("{}/raw.rs", out_dir))
+    .arg(format!("-I{}/Src", LIBOVR_PATH))
+    .arg("scripts/ovr_bindings.h")
+    .status()
+    .unwrap();
+  assert!(status.success(), "running {} failed", bindgen);
+}
+
+fn main() {
+  let out_dir = getenv("OUT_DIR");
+  let target = getenv("TARGET");
+
+  // with the dynamic feature libovr is loaded at runtime instead of linked
+  let dynamic = os::getenv("CARGO_FEATURE_DYNAMIC").is_some();
+
+  if target.as_slice().contains("linux") {
+    if !dynamic && !link_system_libovr() {
+      build_libovr();
+      for lib in LINUX_LIBS.iter() {
+        link(*lib, "dylib");
+      }
+    }
+    build_layout_shim(out_dir.as_slice());
+    generate_bindings(out_dir.as_slice());
+  } else if target.as_slice().contains("darwin") && !dynamic {
+    // Only the Linux SDK is vendored, link a prebuilt libovr.a.
+    match os::getenv("LIBOVR_LIB_DIR") {
+      Some(dir) => link_search(dir.as_slice()),
+      None => panic!("only the Linux SDK is vendored: set LIBOVR_LIB_DIR to a directory \
+                      containing libovr.a, or enable the dynamic feature"),
+    }
+    link("ovr", "static");
+    link("stdc++", "dylib");
+    for framework in MACOS_FRAMEWORKS.iter() {
+      link(*framework, "framework");
+    }
+  }
+}
@@ -0,0 +1,16 @@
+/*
+ * Input header for bindgen (see build.rs).
+ *
+ * OVR_CAPI_GL.h uses the C++ struct name `_XDisplay` without the `struct`
+ * keyword, so provide the typedef to make it parse as C.
+ */
+
+#define OVR_OS_LINUX
+
+#include <GL/gl.h>
+#include <GL/glx.h>
+
+typedef struct _XDisplay _XDisplay;
+
+#include "OVR_CAPI.h"
+#include "OVR_CAPI_GL.h"
@@ -4,7 +4,10 @@ use super::ffi;
 use libc::{c_int, c_uint, c_void, c_float, c_double};
 use std::string::raw::from_buf;
 use std::default::Default;
-use std::c_str::CString;
+use std::c_str::ToCStr;
+use std::ptr;
+
+use super::distortion::Mesh;
 
 pub use ffi::TrackingState;
 pub use ffi::FovPort;
@@ -17,6 +20,8 @@ pub use ffi::Vector3f;
 pub use ffi::Posef;
 pub use ffi::Matrix4f;
 pub use ffi::Vector2f;
+pub use ffi::HmdDesc;
+pub use ffi::Texture;
 
 //-----------------------------------------------------------------------------------
 // Enum wrappers
@@ -31,7 +36,7 @@ pub enum HmdType {
   HmdOther,
 }
 impl HmdType {
-  fn from_ffi(c: c_uint) -> HmdType {
+  pub fn from_ffi(c: c_uint) -> HmdType {
     match c {
       ffi::Hmd_None       => HmdNone,
       ffi::Hmd_DK1        => HmdDK1,
@@ -40,7 +45,7 @@ impl HmdType {
       _                   => HmdOther  
     }
   }
-  fn to_ffi(&self) -> c_uint {
+  pub fn to_ffi(&self) -> c_uint {
     match *self {
       HmdNone             => ffi::Hmd_None,
       HmdDK1              => ffi::Hmd_DK1,
@@ -58,14 +63,14 @@ pub enum EyeType {
 }
 
 impl EyeType {
-  fn from_ffi(c: c_uint) -> EyeType {
+  pub fn from_ffi(c: c_uint) -> EyeType {
     match c {
       ffi::Eye_Left  => EyeL,
       ffi::Eye_Right => EyeR,
       _ => panic!("Invalid eye type {}", c)
     }
   }
-  fn to_ffi(&self) -> c_uint {
+  pub fn to_ffi(&self) -> c_uint {
     match *self {
       EyeL => ffi::Eye_Left,
       EyeR => ffi::Eye_Right
@@ -192,20 +197,55 @@ impl HmdCaps {
 // Wrapper for general OVR library
 //-----------------------------------------------------------------------------------
 
+#[deriving(PartialEq, Show)]
+pub enum InitError {
+  /// The libovr shared library or one of its functions could not be loaded
+  /// (only with the `dynamic` feature).
+  RuntimeNotAvailable(String),
+  /// ovr_Initialize failed.
+  InitializeFailed,
+}
+
+#[cfg(feature = "dynamic")]
+fn load_runtime() -> Result<(), InitError> {
+  match ::dynamic::load() {
+    Ok(_)  => Ok(()),
+    Err(e) => Err(RuntimeNotAvailable(e)),
+  }
+}
+
+#[cfg(not(feature = "dynamic"))]
+fn load_runtime() -> Result<(), InitError> {
+  Ok(())
+}
+
 pub struct Ovr;
 
 impl Ovr {
 
   pub fn initialize() -> Option<Ovr> {
+    Ovr::try_initialize().ok()
+  }
+
+  /// Like `initialize`, but tells apart a missing runtime from a failing
+  /// initialization. With the `dynamic` feature this loads libovr first; the
+  /// stateless functions below load it on first use instead, and panic if
+  /// it is missing.
+  pub fn try_initialize() -> Result<Ovr, InitError> {
+    try!(load_runtime());
     unsafe {
       if ffi::ovr_Initialize() != 0i8 {
-        Some(Ovr)
+        Ok(Ovr)
       } else {
-        None
+        Err(InitializeFailed)
       }
     }
   }
 
+  pub fn get_version_string(&self) -> String {
+    unsafe { from_buf(ffi::ovr_GetVersionString() as *const u8) }
+  }
+
   pub fn detect(&self) -> int {
     unsafe { ffi::ovrHmd_Detect() as int }
   }
@@ -282,6 +322,17 @@ pub struct Hmd {
 
 impl Hmd {
 
+  /// The SDK's ovrHmd handle is a pointer to the HMD description.
+  pub fn get_desc(&self) -> &HmdDesc {
+    unsafe { &*(self.ptr as *const HmdDesc) }
+  }
+
+  /// Preferred eye rendering order for best performance.
+  pub fn get_eye_render_order(&self) -> [EyeType, ..2] {
+    let order = self.get_desc().EyeRenderOrder;
+    [EyeType::from_ffi(order[0]), EyeType::from_ffi(order[1])]
+  }
+
   pub fn get_last_error(&self) -> Option<String> {
     unsafe {
       let ptr = ffi::ovrHmd_GetLastError(self.ptr);
@@ -292,6 +343,13 @@ impl Hmd {
     }
   }
 
+  /// Reads a float property, e.g. `ffi::KEY_EYE_HEIGHT` from the user profile.
+  pub fn get_float(&self, property_name: &str, default_val: f32) -> f32 {
+    property_name.with_c_str(|name| unsafe {
+      ffi::ovrHmd_GetFloat(self.ptr, name, default_val as c_float) as f32
+    })
+  }
+
   /// TODO
   pub fn attach_to_window() {
     unimplemented!()
@@ -359,7 +417,13 @@ impl Hmd {
     }
   }
 
-  pub fn get_eye_poses(&self, frame_index: i32, hmd_to_eye_view_offset: [Vector3f, ..2]) {
+  pub fn end_frame(&self, render_pose: [Posef, ..2], eye_texture: [Texture, ..2]) {
+    unsafe {
+      ffi::ovrHmd_EndFrame(self.ptr, &render_pose[0], &eye_texture[0]);
+    }
+  }
+
+  pub fn get_eye_poses(&self, frame_index: i32, hmd_to_eye_view_offset: [Vector3f, ..2]) -> ([Posef, ..2], TrackingState) {
     unsafe {
       let mut out_eye_poses: [Posef, ..2] = [Default::default(), Default::default()];
       let out_eye_poses_ptr: *mut Posef = &mut out_eye_poses[0];
@@ -369,15 +433,54 @@ impl Hmd {
                               &hmd_to_eye_view_offset[0],
                               out_eye_poses_ptr,
                               &mut out_hmd_tracking_state);
+      (out_eye_poses, out_hmd_tracking_state)
     }
   }
 
+  /// Returns the predicted head pose to use when rendering the specified eye.
+  /// Deprecated in the SDK in favor of `get_eye_poses`, but useful for renderers
+  /// which render the eyes one after another. The caller must still apply
+  /// `HmdToEyeViewOffset`.
+  pub fn get_hmd_pose_per_eye(&self, eye: EyeType) -> Posef {
+    unsafe {
+      ffi::ovrHmd_GetHmdPosePerEye(self.ptr, eye.to_ffi())
+    }
+  }
+
+  /// Iterates over the eyes in the preferred `EyeRenderOrder`. The pose of each
+  /// eye is queried by `get_hmd_pose_per_eye` only when the eye is reached,
+  /// so it is as recent as possible.
+  pub fn iter_eyes<'a>(&'a self) -> EyePoses<'a> {
+    EyePoses{ hmd: self, index: 0 }
+  }
+
   pub fn get_render_desc(&self, eye: EyeType, fov: FovPort) -> EyeRenderDesc {
     unsafe {
       ffi::ovrHmd_GetRenderDesc(self.ptr, eye.to_ffi(), fov)
     }
   }
 
+  /// Generates the distortion mesh of an eye for client distortion
+  /// rendering. The mesh is copied and the SDK's allocation freed again.
+  pub fn create_distortion_mesh(&self, eye: EyeType, fov: FovPort, distortion_caps: DistortionCaps) -> Option<Mesh> {
+    unsafe {
+      let mut mesh_data = ffi::DistortionMesh{
+        pVertexData: ptr::null_mut(),
+        pIndexData: ptr::null_mut(),
+        VertexCount: 0,
+        IndexCount: 0,
+      };
+      match ffi::ovrHmd_CreateDistortionMesh(self.ptr, eye.to_ffi(), fov, distortion_caps.mask, &mut mesh_data) {
+        0 => None,
+        _ => {
+          let mesh = Mesh::from_ffi(&mesh_data);
+          ffi::ovrHmd_DestroyDistortionMesh(&mut mesh_data);
+          Some(mesh)
+        }
+      }
+    }
+  }
+
   pub fn get_frame_timing(&self, frame_index: i32) -> FrameTiming {
     unsafe {
       ffi::ovrHmd_GetFrameTiming(self.ptr, frame_index as c_uint)
@@ -402,10 +505,11 @@ impl Hmd {
     }
   }
 
-  pub fn get_eye_timewarp_matrices(&self, eye: EyeType, render_pose: Posef) -> Matrix4f {
+  /// Timewarp matrices for the start and the end of the eye's scanout.
+  pub fn get_eye_timewarp_matrices(&self, eye: EyeType, render_pose: Posef) -> [Matrix4f, ..2] {
     unsafe {
-      let mut twm_out = Matrix4f{ M: [[0f32 as c_float, ..4], ..4] };
-      ffi::ovrHmd_GetEyeTimewarpMatrices(self.ptr, eye.to_ffi(), render_pose, &mut twm_out);
+      let mut twm_out = [Matrix4f{ M: [[0f32 as c_float, ..4], ..4] }, ..2];
+      ffi::ovrHmd_GetEyeTimewarpMatrices(self.ptr, eye.to_ffi(), render_pose, twm_out.as_mut_ptr());
       twm_out
     }
   }
@@ -413,30 +517,62 @@ impl Hmd {
 // -----------------------------------------------------------------------------------
 // ***** Latency Test interface
 
-  pub fn process_latency_test(&self, rgb_color_out: [u8, ..3]) -> bool {
+  /// Runs the latency tester for this frame. Returns the color to clear the
+  /// screen with while a test is running.
+  pub fn process_latency_test(&self) -> Option<[u8, ..3]> {
+    let mut rgb = [0u8, ..3];
     unsafe {
-      ffi::ovrHmd_ProcessLatencyTest(self.ptr, &rgb_color_out[0]) == 1i8
+      match ffi::ovrHmd_ProcessLatencyTest(self.ptr, rgb.as_mut_ptr()) {
+        0 => None,
+        _ => Some(rgb),
+      }
     }
   }
 
-  pub fn get_latency_test_result(&self) -> String {
+  /// Returns the result of a finished latency test once, None otherwise.
+  pub fn get_latency_test_result(&self) -> Option<String> {
     unsafe {
-      let c_ptr = ffi::ovrHmd_GetLatencyTestResult(self.ptr);
-      let s = CString::new(c_ptr, false);
-      let s_slice = s.as_str().unwrap();
-      s_slice.to_string()
+      let ptr = ffi::ovrHmd_GetLatencyTestResult(self.ptr);
+      match ptr.is_null() {
+        true  => None,
+        false => Some(from_buf(ptr as *const u8)),
+      }
     }
   }
 
-  pub fn get_latency_test_draw_color(&self, rgb_color_out: &mut [u8, ..3]) {
+  /// The color of the DK2 latency tester quad, None if the test is disabled
+  /// or not applicable (e.g. on a DK1).
+  pub fn get_latency_test_draw_color(&self) -> Option<[u8, ..3]> {
+    let mut rgb = [0u8, ..3];
     unsafe {
-      ffi::ovrHmd_GetLatencyTest2DrawColor(self.ptr, rgb_color_out);
+      match ffi::ovrHmd_GetLatencyTest2DrawColor(self.ptr, rgb.as_mut_ptr()) {
+        0 => None,
+        _ => Some(rgb),
+      }
     }
   }
 
   
 }
 
+/// Iterator returned by `Hmd::iter_eyes`.
+pub struct EyePoses<'a> {
+  hmd: &'a Hmd,
+  index: uint,
+}
+
+impl<'a> Iterator<(EyeType, Posef)> for EyePoses<'a> {
+  fn next(&mut self) -> Option<(EyeType, Posef)> {
+    if self.index >= 2 {
+      return None;
+    }
+    let eye = self.hmd.get_desc().EyeRenderOrder[self.index];
+    self.index += 1;
+    let pose = self.hmd.get_hmd_pose_per_eye(EyeType::from_ffi(eye));
+    Some((EyeType::from_ffi(eye), pose))
+  }
+}
+
 impl Drop for Hmd {
   fn drop(&mut self) {
     unsafe {ffi::ovrHmd_Destroy(self.ptr)}
@@ -0,0 +1,108 @@
+//! Conversions to and from `cgmath` types.
+//!
+//! `cgmath` stores matrices column major while `Matrix4f::M` is row major,
+//! so matrix conversions transpose. Poses map to `Decomposed` transforms with
+//! unit scale. A `FovPort` has no depth range, see `fov_to_perspective` for
+//! the conversion to a `Perspective` frustum.
+
+use cgmath::{Vector2, Vector3, Vector4, Quaternion, Matrix4, Decomposed, Perspective};
+
+use ffi::{Vector2f, Vector3f, Quatf, Matrix4f, Posef, FovPort};
+use convert::{ToMath, ToOvr};
+
+pub type Isometry = Decomposed<f32, Vector3<f32>, Quaternion<f32>>;
+
+impl ToMath<Vector2<f32>> for Vector2f {
+  fn to_math(&self) -> Vector2<f32> {
+    Vector2::new(self.x, self.y)
+  }
+}
+
+impl ToOvr<Vector2f> for Vector2<f32> {
+  fn to_ovr(&self) -> Vector2f {
+    Vector2f{ x: self.x, y: self.y }
+  }
+}
+
+impl ToMath<Vector3<f32>> for Vector3f {
+  fn to_math(&self) -> Vector3<f32> {
+    Vector3::new(self.x, self.y, self.z)
+  }
+}
+
+impl ToOvr<Vector3f> for Vector3<f32> {
+  fn to_ovr(&self) -> Vector3f {
+    Vector3f{ x: self.x, y: self.y, z: self.z }
+  }
+}
+
+impl ToMath<Quaternion<f32>> for Quatf {
+  fn to_math(&self) -> Quaternion<f32> {
+    Quaternion::new(self.w, self.x, self.y, self.z)
+  }
+}
+
+impl ToOvr<Quatf> for Quaternion<f32> {
+  fn to_ovr(&self) -> Quatf {
+    Quatf{ x: self.v.x, y: self.v.y, z: self.v.z, w: self.s }
+  }
+}
+
+impl ToMath<Matrix4<f32>> for Matrix4f {
+  fn to_math(&self) -> Matrix4<f32> {
+    let m = &self.M;
+    Matrix4::from_cols(Vector4::new(m[0][0], m[1][0], m[2][0], m[3][0]),
+                       Vector4::new(m[0][1], m[1][1], m[2][1], m[3][1]),
+                       Vector4::new(m[0][2], m[1][2], m[2][2], m[3][2]),
+                       Vector4::new(m[0][3], m[1][3], m[2][3], m[3][3]))
+  }
+}
+
+impl ToOvr<Matrix4f> for Matrix4<f32> {
+  fn to_ovr(&self) -> Matrix4f {
+    let (x, y, z, w) = (&self.x, &self.y, &self.z, &self.w);
+    Matrix4f{ M: [[x.x, y.x, z.x, w.x],
+                  [x.y, y.y, z.y, w.y],
+                  [x.z, y.z, z.z, w.z],
+                  [x.w, y.w, z.w, w.w]] }
+  }
+}
+
+impl ToMath<Isometry> for Posef {
+  fn to_math(&self) -> Isometry {
+    Decomposed{
+      scale: 1.0,
+      rot: self.Orientation.to_math(),
+      disp: self.Position.to_math(),
+    }
+  }
+}
+
+impl ToOvr<Posef> for Isometry {
+  fn to_ovr(&self) -> Posef {
+    Posef{ Orientation: self.rot.to_ovr(), Position: self.disp.to_ovr() }
+  }
+}
+
+/// The frustum of `fov` between the `near` and `far` distances.
+pub fn fov_to_perspective(fov: &FovPort, near: f32, far: f32) -> Perspective<f32> {
+  Perspective{
+    left: -fov.LeftTan * near,
+    right: fov.RightTan * near,
+    bottom: -fov.DownTan * near,
+    top: fov.UpTan * near,
+    near: near,
+    far: far,
+  }
+}
+
+impl ToOvr<FovPort> for Perspective<f32> {
+  fn to_ovr(&self) -> FovPort {
+    FovPort{
+      UpTan: self.top / self.near,
+      DownTan: -self.bottom / self.near,
+      LeftTan: -self.left / self.near,
+      RightTan: self.right / self.near,
+    }
+  }
+}
@@ -0,0 +1,110 @@
+//! Sources of time for the helpers that wait or sample at absolute times.
+//!
+//! Everything that reads the current time or waits takes a `Clock`, so it
+//! can run on the SDK clock in an application and on a `SimulatedClock` in
+//! tests.
+
+use std::cell::Cell;
+use std::io::timer;
+use std::time::Duration;
+use time;
+
+use api::Ovr;
+
+/// Absolute time in seconds, on the same time base as the `FrameTiming`
+/// and `TrackingState` times the clock is used with.
+pub trait Clock {
+  fn now(&self) -> f64;
+
+  /// Blocks until `abs_time` and returns the time after waiting. Returns
+  /// immediately if `abs_time` has passed.
+  fn wait_until(&self, abs_time: f64) -> f64;
+}
+
+/// The SDK clock, `ovr_GetTimeInSeconds` and `ovr_WaitTillTime`.
+pub struct SdkClock;
+
+impl Clock for SdkClock {
+  fn now(&self) -> f64 {
+    Ovr::get_time_in_seconds()
+  }
+
+  fn wait_until(&self, abs_time: f64) -> f64 {
+    Ovr::wait_till_time(abs_time);
+    self.now()
+  }
+}
+
+/// The monotonic clock of the operating system, in seconds since the clock
+/// was created. It does not share the time base of the SDK, so use it with
+/// mock HMDs and application logic rather than with `FrameTiming` of a real
+/// HMD.
+pub struct SystemClock {
+  origin: u64,
+}
+
+impl SystemClock {
+
+  pub fn new() -> SystemClock {
+    SystemClock{ origin: time::precise_time_ns() }
+  }
+}
+
+impl Clock for SystemClock {
+  fn now(&self) -> f64 {
+    (time::precise_time_ns() - self.origin) as f64 * 1e-9
+  }
+
+  fn wait_until(&self, abs_time: f64) -> f64 {
+    let remaining = abs_time - self.now();
+    if remaining > 0.0 {
+      timer::sleep(Duration::nanoseconds((remaining * 1e9) as i64));
+    }
+    self.now()
+  }
+}
+
+/// A clock that only moves when told to. Waiting jumps to the requested
+/// time, so code that paces itself runs instantly and deterministically.
+pub struct SimulatedClock {
+  time: Cell<f64>,
+}
+
+impl SimulatedClock {
+
+  pub fn new(start: f64) -> SimulatedClock {
+    SimulatedClock{ time: Cell::new(start) }
+  }
+
+  pub fn set(&self, abs_time: f64) {
+    self.time.set(abs_time);
+  }
+
+  /// Moves the clock forward, e.g. to simulate the time spent rendering.
+  pub fn advance(&self, seconds: f64) {
+    self.time.set(self.time.get() + seconds);
+  }
+}
+
+impl Clock for SimulatedClock {
+  fn now(&self) -> f64 {
+    self.time.get()
+  }
+
+  fn wait_until(&self, abs_time: f64) -> f64 {
+    if abs_time > self.time.get() {
+      self.time.set(abs_time);
+    }
+    self.time.get()
+  }
+}
+
+impl<'a, C: Clock> Clock for &'a C {
+  fn now(&self) -> f64 {
+    (**self).now()
+  }
+
+  fn wait_until(&self, abs_time: f64) -> f64 {
+    (**self).wait_until(abs_time)
+  }
+}
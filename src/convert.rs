@@ -0,0 +1,18 @@
+//! Conversion traits between the ffi math structures and the types of
+//! external math libraries. The implementations are enabled by the optional
+//! `cgmath` and `nalgebra` cargo features, in `cgmath_conv` and
+//! `nalgebra_conv`.
+//!
+//! The standard library of the supported Rust version has no generic
+//! conversion traits like `From`/`Into` yet, so the conversions have their
+//! own traits.
+
+/// Converts an ffi value into the corresponding type `T` of a math library.
+pub trait ToMath<T> {
+  fn to_math(&self) -> T;
+}
+
+/// Converts a math library value back into the ffi type `T`.
+pub trait ToOvr<T> {
+  fn to_ovr(&self) -> T;
+}
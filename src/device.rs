@@ -0,0 +1,72 @@
+//! The HMD operations as a trait, so application code can run against the
+//! real device (`api::Hmd`) or the software `mock::MockHmd`.
+
+use api::{Hmd, EyeType, HmdCaps, TrackingCaps, DistortionCaps};
+use ffi::{HmdDesc, TrackingState, FovPort, Sizei, EyeRenderDesc, FrameTiming, Posef, Vector3f,
+          RenderAPIConfig, Texture, Matrix4f};
+
+pub trait HmdDevice {
+  fn get_desc(&self) -> &HmdDesc;
+  fn get_last_error(&self) -> Option<String>;
+  fn get_float(&self, property_name: &str, default_val: f32) -> f32;
+  fn get_enabled_caps(&self) -> HmdCaps;
+  fn set_enabled_caps(&self, caps: HmdCaps);
+  fn configure_tracking(&self, supported_tracking_caps: TrackingCaps, required_tracking_caps: TrackingCaps) -> bool;
+  fn recenter_pose(&self);
+  fn get_tracking_state(&self, abs_time: f64) -> TrackingState;
+  fn get_fov_texture_size(&self, eye: EyeType, fov: FovPort, pixels_per_display_pixel: f32) -> Sizei;
+  fn get_render_desc(&self, eye: EyeType, fov: FovPort) -> EyeRenderDesc;
+  fn configure_rendering(&self, api_config: RenderAPIConfig, distortion_caps: DistortionCaps,
+                         eye_fov_in: [FovPort, ..2]) -> [EyeRenderDesc, ..2];
+  fn begin_frame(&self, frame_index: i32) -> FrameTiming;
+  fn end_frame(&self, render_pose: [Posef, ..2], eye_texture: [Texture, ..2]);
+  fn get_eye_poses(&self, frame_index: i32, hmd_to_eye_view_offset: [Vector3f, ..2]) -> ([Posef, ..2], TrackingState);
+  fn get_hmd_pose_per_eye(&self, eye: EyeType) -> Posef;
+  fn get_frame_timing(&self, frame_index: i32) -> FrameTiming;
+  fn begin_frame_timing(&self, frame_index: i32) -> FrameTiming;
+  fn end_frame_timing(&self);
+  fn reset_frame_timing(&self, frame_index: i32);
+  fn get_eye_timewarp_matrices(&self, eye: EyeType, render_pose: Posef) -> [Matrix4f, ..2];
+  fn process_latency_test(&self) -> Option<[u8, ..3]>;
+  fn get_latency_test_result(&self) -> Option<String>;
+  fn get_latency_test_draw_color(&self) -> Option<[u8, ..3]>;
+}
+
+impl HmdDevice for Hmd {
+  fn get_desc(&self) -> &HmdDesc { self.get_desc() }
+  fn get_last_error(&self) -> Option<String> { self.get_last_error() }
+  fn get_float(&self, property_name: &str, default_val: f32) -> f32 { self.get_float(property_name, default_val) }
+  fn get_enabled_caps(&self) -> HmdCaps { self.get_enabled_caps() }
+  fn set_enabled_caps(&self, caps: HmdCaps) { self.set_enabled_caps(caps) }
+  fn configure_tracking(&self, supported_tracking_caps: TrackingCaps, required_tracking_caps: TrackingCaps) -> bool {
+    self.configure_tracking(supported_tracking_caps, required_tracking_caps)
+  }
+  fn recenter_pose(&self) { self.recenter_pose() }
+  fn get_tracking_state(&self, abs_time: f64) -> TrackingState { self.get_tracking_state(abs_time) }
+  fn get_fov_texture_size(&self, eye: EyeType, fov: FovPort, pixels_per_display_pixel: f32) -> Sizei {
+    self.get_fov_texture_size(eye, fov, pixels_per_display_pixel)
+  }
+  fn get_render_desc(&self, eye: EyeType, fov: FovPort) -> EyeRenderDesc { self.get_render_desc(eye, fov) }
+  fn configure_rendering(&self, api_config: RenderAPIConfig, distortion_caps: DistortionCaps,
+                         eye_fov_in: [FovPort, ..2]) -> [EyeRenderDesc, ..2] {
+    self.configure_rendering(api_config, distortion_caps, eye_fov_in)
+  }
+  fn begin_frame(&self, frame_index: i32) -> FrameTiming { self.begin_frame(frame_index) }
+  fn end_frame(&self, render_pose: [Posef, ..2], eye_texture: [Texture, ..2]) {
+    self.end_frame(render_pose, eye_texture)
+  }
+  fn get_eye_poses(&self, frame_index: i32, hmd_to_eye_view_offset: [Vector3f, ..2]) -> ([Posef, ..2], TrackingState) {
+    self.get_eye_poses(frame_index, hmd_to_eye_view_offset)
+  }
+  fn get_hmd_pose_per_eye(&self, eye: EyeType) -> Posef { self.get_hmd_pose_per_eye(eye) }
+  fn get_frame_timing(&self, frame_index: i32) -> FrameTiming { self.get_frame_timing(frame_index) }
+  fn begin_frame_timing(&self, frame_index: i32) -> FrameTiming { self.begin_frame_timing(frame_index) }
+  fn end_frame_timing(&self) { self.end_frame_timing() }
+  fn reset_frame_timing(&self, frame_index: i32) { self.reset_frame_timing(frame_index) }
+  fn get_eye_timewarp_matrices(&self, eye: EyeType, render_pose: Posef) -> [Matrix4f, ..2] {
+    self.get_eye_timewarp_matrices(eye, render_pose)
+  }
+  fn process_latency_test(&self) -> Option<[u8, ..3]> { self.process_latency_test() }
+  fn get_latency_test_result(&self) -> Option<String> { self.get_latency_test_result() }
+  fn get_latency_test_draw_color(&self) -> Option<[u8, ..3]> { self.get_latency_test_draw_color() }
+}
@@ -0,0 +1,320 @@
+//! CPU reference implementation of distortion rendering.
+//!
+//! Rasterizes the SDK's distortion mesh the way the distortion shaders do:
+//! the tan eye angles of the mesh are interpolated across every triangle,
+//! mapped to the source image with `EyeToSourceUVScale` and
+//! `EyeToSourceUVOffset`, sampled bilinearly per color channel and
+//! multiplied with the vignette. No GPU is involved, so the output for the
+//! debug HMDs can be compared against reference images on any machine.
+//!
+//! Images are 8 bit RGB with rows from top to bottom, as seen on the HMD's
+//! screen. The source is a side-by-side stereo image, the left eye in the
+//! left half. Timewarp is not applied.
+
+use std::cmp::min;
+use std::io;
+use std::io::{IoResult, IoError, File, BufferedReader, BufferedWriter};
+use std::num::Float;
+
+use api::{Hmd, EyeType, EyeL, EyeR, DistortionCaps};
+use ffi;
+use ffi::{DistortionVertex, FovPort, Recti, Sizei, Vector2f, Vector2i};
+
+/// An 8 bit RGB image.
+#[deriving(Clone, PartialEq)]
+pub struct Image {
+  pub width: uint,
+  pub height: uint,
+  /// Rows from top to bottom, 3 bytes per pixel.
+  pub data: Vec<u8>,
+}
+
+fn invalid(desc: &'static str) -> IoError {
+  IoError{ kind: io::InvalidInput, desc: desc, detail: None }
+}
+
+impl Image {
+
+  /// A black image.
+  pub fn new(width: uint, height: uint) -> Image {
+    Image{ width: width, height: height, data: Vec::from_elem(width * height * 3, 0u8) }
+  }
+
+  /// Returns None if `data` does not hold `width * height` pixels.
+  pub fn from_data(width: uint, height: uint, data: Vec<u8>) -> Option<Image> {
+    if data.len() == width * height * 3 {
+      Some(Image{ width: width, height: height, data: data })
+    } else {
+      None
+    }
+  }
+
+  pub fn get(&self, x: uint, y: uint) -> [u8, ..3] {
+    let i = (y * self.width + x) * 3;
+    [self.data[i], self.data[i + 1], self.data[i + 2]]
+  }
+
+  pub fn set(&mut self, x: uint, y: uint, rgb: [u8, ..3]) {
+    let i = (y * self.width + x) * 3;
+    self.data[i] = rgb[0];
+    self.data[i + 1] = rgb[1];
+    self.data[i + 2] = rgb[2];
+  }
+
+  /// Reads a binary PPM (P6) image with a maximum value of 255.
+  pub fn read_ppm<R: Reader>(reader: &mut R) -> IoResult<Image> {
+    let bytes = try!(reader.read_to_end());
+    let mut pos = 0u;
+    let mut header = [0u, ..3];
+    if bytes.len() < 2 || bytes[0] != b'P' || bytes[1] != b'6' {
+      return Err(invalid("not a binary PPM image"));
+    }
+    pos += 2;
+    for value in header.iter_mut() {
+      // whitespace and comments between the header fields
+      loop {
+        if pos >= bytes.len() {
+          return Err(invalid("truncated PPM header"));
+        }
+        match bytes[pos] {
+          b' ' | b'\t' | b'\r' | b'\n' => pos += 1,
+          b'#' => while pos < bytes.len() && bytes[pos] != b'\n' { pos += 1 },
+          _ => break,
+        }
+      }
+      let start = pos;
+      while pos < bytes.len() && bytes[pos] >= b'0' && bytes[pos] <= b'9' {
+        *value = *value * 10 + (bytes[pos] - b'0') as uint;
+        pos += 1;
+      }
+      if pos == start {
+        return Err(invalid("invalid PPM header"));
+      }
+    }
+    if header[2] != 255 {
+      return Err(invalid("only PPM images with 8 bits per channel are supported"));
+    }
+    // a single whitespace character separates the header from the pixels
+    let data = bytes.slice_from(min(pos + 1, bytes.len())).to_vec();
+    match Image::from_data(header[0], header[1], data) {
+      Some(image) => Ok(image),
+      None        => Err(invalid("PPM image data has the wrong size")),
+    }
+  }
+
+  pub fn write_ppm<W: Writer>(&self, writer: &mut W) -> IoResult<()> {
+    try!(write!(writer, "P6\n{} {}\n255\n", self.width, self.height));
+    writer.write(self.data.as_slice())
+  }
+
+  pub fn open(path: &Path) -> IoResult<Image> {
+    let file = try!(File::open(path));
+    Image::read_ppm(&mut BufferedReader::new(file))
+  }
+
+  pub fn save(&self, path: &Path) -> IoResult<()> {
+    let file = try!(File::create(path));
+    let mut writer = BufferedWriter::new(file);
+    try!(self.write_ppm(&mut writer));
+    writer.flush()
+  }
+
+  /// Number of pixels with a channel differing by more than `tolerance`
+  /// from `other`, None if the sizes differ.
+  pub fn count_differences(&self, other: &Image, tolerance: u8) -> Option<uint> {
+    if self.width != other.width || self.height != other.height {
+      return None;
+    }
+    let count = self.data.as_slice().chunks(3).zip(other.data.as_slice().chunks(3)).filter(|&(a, b)| {
+      a.iter().zip(b.iter()).any(|(&a, &b)| if a > b { a - b > tolerance } else { b - a > tolerance })
+    }).count();
+    Some(count)
+  }
+
+  /// Samples channel `c` at the texture coordinates `uv` with bilinear
+  /// filtering, clamping to the edges like `GL_CLAMP_TO_EDGE`.
+  fn sample(&self, u: f32, v: f32, c: uint) -> f32 {
+    let x = u * self.width as f32 - 0.5;
+    let y = v * self.height as f32 - 0.5;
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let texel = |x: f32, y: f32| -> f32 {
+      let x = x.max(0.0).min(self.width as f32 - 1.0) as uint;
+      let y = y.max(0.0).min(self.height as f32 - 1.0) as uint;
+      self.data[(y * self.width + x) * 3 + c] as f32
+    };
+    let top = texel(x0, y0) * (1.0 - fx) + texel(x0 + 1.0, y0) * fx;
+    let bottom = texel(x0, y0 + 1.0) * (1.0 - fx) + texel(x0 + 1.0, y0 + 1.0) * fx;
+    top * (1.0 - fy) + bottom * fy
+  }
+}
+
+/// A distortion mesh copied out of the SDK's `ovrDistortionMesh`.
+#[deriving(Clone, Show)]
+pub struct Mesh {
+  pub vertices: Vec<DistortionVertex>,
+  /// Triangle list.
+  pub indices: Vec<u16>,
+}
+
+impl Mesh {
+
+  /// Copies the vertices and indices, the SDK's mesh still has to be freed
+  /// with `ovrHmd_DestroyDistortionMesh`.
+  pub unsafe fn from_ffi(mesh: &ffi::DistortionMesh) -> Mesh {
+    if mesh.pVertexData.is_null() || mesh.pIndexData.is_null() {
+      return Mesh{ vertices: Vec::new(), indices: Vec::new() };
+    }
+    Mesh{
+      vertices: Vec::from_raw_buf(mesh.pVertexData as *const DistortionVertex, mesh.VertexCount as uint),
+      indices: Vec::from_raw_buf(mesh.pIndexData as *const u16, mesh.IndexCount as uint),
+    }
+  }
+}
+
+/// The distortion mesh of an eye and where the eye is in the source image.
+#[deriving(Clone, Show)]
+pub struct EyeDistortion {
+  pub mesh: Mesh,
+  /// `EyeToSourceUVScale` and `EyeToSourceUVOffset`.
+  pub uv_scale_offset: [Vector2f, ..2],
+}
+
+impl EyeDistortion {
+
+  /// For an eye rendered with `fov` into its half of a side-by-side source
+  /// image of the given size.
+  pub fn new(mesh: Mesh, eye: EyeType, fov: &FovPort, source_width: uint, source_height: uint) -> EyeDistortion {
+    let size = Sizei{ x: source_width as i32, y: source_height as i32 };
+    let viewport = side_by_side_viewport(eye, source_width, source_height);
+    EyeDistortion{ mesh: mesh, uv_scale_offset: render_scale_and_offset(fov, &size, &viewport) }
+  }
+
+  /// Creates the mesh of `eye` for the HMD's default FOV.
+  pub fn from_hmd(hmd: &Hmd, eye: EyeType, caps: DistortionCaps,
+                  source_width: uint, source_height: uint) -> Option<EyeDistortion> {
+    let fov = hmd.get_desc().DefaultEyeFov[eye.to_ffi() as uint].clone();
+    hmd.create_distortion_mesh(eye, fov.clone(), caps).map(|mesh| {
+      EyeDistortion::new(mesh, eye, &fov, source_width, source_height)
+    })
+  }
+}
+
+/// The viewport of an eye in a side-by-side image.
+pub fn side_by_side_viewport(eye: EyeType, width: uint, height: uint) -> Recti {
+  let half = width / 2;
+  let (x, w) = match eye {
+    EyeL => (0, half),
+    EyeR => (half, width - half),
+  };
+  Recti{ pos: Vector2i{ x: x as i32, y: 0 }, size: Sizei{ x: w as i32, y: height as i32 } }
+}
+
+/// Port of `ovrHmd_GetRenderScaleAndOffset`: the scale and offset from tan
+/// eye angles to the UVs of a texture of `texture_size`, for an eye
+/// rendered with `fov` into `viewport`.
+pub fn render_scale_and_offset(fov: &FovPort, texture_size: &Sizei, viewport: &Recti) -> [Vector2f, ..2] {
+  // CreateNDCScaleAndOffsetFromFov
+  let x_scale = 2.0 / (fov.LeftTan + fov.RightTan);
+  let x_offset = (fov.LeftTan - fov.RightTan) * x_scale * 0.5;
+  let y_scale = 2.0 / (fov.UpTan + fov.DownTan);
+  let y_offset = (fov.UpTan - fov.DownTan) * y_scale * 0.5;
+  // CreateUVScaleAndOffsetfromNDCScaleandOffset
+  let (tw, th) = (texture_size.x as f32, texture_size.y as f32);
+  let (sx, sy) = (viewport.size.x as f32 / tw, viewport.size.y as f32 / th);
+  let (ox, oy) = (viewport.pos.x as f32 / tw, viewport.pos.y as f32 / th);
+  [Vector2f{ x: x_scale * 0.5 * sx, y: y_scale * 0.5 * sy },
+   Vector2f{ x: (x_offset * 0.5 + 0.5) * sx + ox, y: (y_offset * 0.5 + 0.5) * sy + oy }]
+}
+
+/// Renders both eyes of the HMD at its screen resolution, with the default
+/// FOV. Returns None if the SDK fails to create a mesh.
+pub fn render_hmd(hmd: &Hmd, source: &Image, caps: DistortionCaps) -> Option<Image> {
+  let resolution = hmd.get_desc().Resolution.clone();
+  let mut target = Image::new(resolution.x as uint, resolution.y as uint);
+  for &eye in [EyeL, EyeR].iter() {
+    match EyeDistortion::from_hmd(hmd, eye, caps, source.width, source.height) {
+      Some(distortion) => render_eye(source, &distortion, caps, &mut target),
+      None             => return None,
+    }
+  }
+  Some(target)
+}
+
+/// Renders both eyes into a black image of the given size.
+pub fn render(source: &Image, eyes: &[EyeDistortion], caps: DistortionCaps, width: uint, height: uint) -> Image {
+  let mut target = Image::new(width, height);
+  for eye in eyes.iter() {
+    render_eye(source, eye, caps, &mut target);
+  }
+  target
+}
+
+/// Rasterizes the mesh of one eye into `target`, leaving pixels outside
+/// the mesh untouched. Uses the green tan eye angles for all channels
+/// without `DistortionCap_Chromatic`, and a vignette of 1 without
+/// `DistortionCap_Vignette`.
+pub fn render_eye(source: &Image, eye: &EyeDistortion, caps: DistortionCaps, target: &mut Image) {
+  let chromatic = caps.mask & ffi::DistortionCap_Chromatic != 0;
+  let vignette = caps.mask & ffi::DistortionCap_Vignette != 0;
+  for triangle in eye.mesh.indices.as_slice().chunks(3) {
+    if triangle.len() == 3 {
+      let v = [&eye.mesh.vertices[triangle[0] as uint],
+               &eye.mesh.vertices[triangle[1] as uint],
+               &eye.mesh.vertices[triangle[2] as uint]];
+      rasterize(source, v, &eye.uv_scale_offset, chromatic, vignette, target);
+    }
+  }
+}
+
+fn rasterize(source: &Image, v: [&DistortionVertex, ..3], uv_scale_offset: &[Vector2f, ..2],
+             chromatic: bool, vignette: bool, target: &mut Image) {
+  let (w, h) = (target.width as f32, target.height as f32);
+  // NDC y points up, image rows go down
+  let p: Vec<[f32, ..2]> = v.iter().map(|v| {
+    [(v.ScreenPosNDC.x + 1.0) * 0.5 * w, (1.0 - v.ScreenPosNDC.y) * 0.5 * h]
+  }).collect();
+  let edge = |a: &[f32, ..2], b: &[f32, ..2], x: f32, y: f32| (b[0] - a[0]) * (y - a[1]) - (b[1] - a[1]) * (x - a[0]);
+  let area = edge(&p[0], &p[1], p[2][0], p[2][1]);
+  if area == 0.0 {
+    return;
+  }
+
+  let min_x = p.iter().fold(w, |m, p| m.min(p[0])).floor().max(0.0) as uint;
+  let max_x = p.iter().fold(0.0, |m, p| m.max(p[0])).ceil().min(w) as uint;
+  let min_y = p.iter().fold(h, |m, p| m.min(p[1])).floor().max(0.0) as uint;
+  let max_y = p.iter().fold(0.0, |m, p| m.max(p[1])).ceil().min(h) as uint;
+
+  let shade: Vec<f32> = v.iter().map(|v| if vignette { v.VignetteFactor.max(0.0) } else { 1.0 }).collect();
+  let uv = |tan: &Vector2f| [tan.x * uv_scale_offset[0].x + uv_scale_offset[1].x,
+                             tan.y * uv_scale_offset[0].y + uv_scale_offset[1].y];
+  // per vertex the UVs of the red, green and blue channel
+  let uvs: Vec<[[f32, ..2], ..3]> = v.iter().map(|v| {
+    if chromatic {
+      [uv(&v.TanEyeAnglesR), uv(&v.TanEyeAnglesG), uv(&v.TanEyeAnglesB)]
+    } else {
+      [uv(&v.TanEyeAnglesG), uv(&v.TanEyeAnglesG), uv(&v.TanEyeAnglesG)]
+    }
+  }).collect();
+
+  for y in range(min_y, max_y) {
+    for x in range(min_x, max_x) {
+      let (cx, cy) = (x as f32 + 0.5, y as f32 + 0.5);
+      // barycentric weights, positive inside for either winding
+      let b0 = edge(&p[1], &p[2], cx, cy) / area;
+      let b1 = edge(&p[2], &p[0], cx, cy) / area;
+      let b2 = edge(&p[0], &p[1], cx, cy) / area;
+      if b0 < 0.0 || b1 < 0.0 || b2 < 0.0 {
+        continue;
+      }
+      let s = shade[0] * b0 + shade[1] * b1 + shade[2] * b2;
+      let mut rgb = [0u8, ..3];
+      for c in range(0u, 3) {
+        let u = uvs[0][c][0] * b0 + uvs[1][c][0] * b1 + uvs[2][c][0] * b2;
+        let v = uvs[0][c][1] * b0 + uvs[1][c][1] * b1 + uvs[2][c][1] * b2;
+        rgb[c] = (source.sample(u, v, c) * s).round().max(0.0).min(255.0) as u8;
+      }
+      target.set(x, y, rgb);
+    }
+  }
+}
@@ -0,0 +1,60 @@
+//! Runtime loading of the libovr shared library, enabled by the `dynamic`
+//! feature. This allows binaries to start on machines without the Oculus
+//! runtime; `Ovr::try_initialize` then reports it as not available.
+
+use std::dynamic_lib::DynamicLibrary;
+use std::mem;
+use std::os;
+use std::sync::{Once, ONCE_INIT};
+
+use ffi::FunctionTable;
+
+#[cfg(target_os = "linux")]
+static DEFAULT_LIBRARY: &'static str = "libovr.so";
+#[cfg(target_os = "macos")]
+static DEFAULT_LIBRARY: &'static str = "libovr.dylib";
+#[cfg(target_os = "windows")]
+static DEFAULT_LIBRARY: &'static str = "libovr.dll";
+
+static LOAD: Once = ONCE_INIT;
+static mut STATE: *const Result<FunctionTable, String> = 0 as *const Result<FunctionTable, String>;
+
+/// Opens the library given by the `LIBOVR_PATH` environment variable, or
+/// the default library name in the system search path, and resolves all
+/// functions. This is done only once; the library stays loaded for the
+/// lifetime of the process.
+pub fn load() -> Result<&'static FunctionTable, String> {
+  LOAD.doit(|| {
+    let result = open_and_resolve();
+    unsafe { STATE = mem::transmute(box result); }
+  });
+  unsafe {
+    match *STATE {
+      Ok(ref table) => Ok(table),
+      Err(ref e)    => Err(e.clone()),
+    }
+  }
+}
+
+fn open_and_resolve() -> Result<FunctionTable, String> {
+  let path = Path::new(os::getenv("LIBOVR_PATH").unwrap_or(DEFAULT_LIBRARY.to_string()));
+  let lib = try!(DynamicLibrary::open(Some(&path))
+    .map_err(|e| format!("could not load {}: {}", path.display(), e)));
+  let table = try!(FunctionTable::load(&lib)
+    .map_err(|e| format!("{} is incomplete: {}", path.display(), e)));
+  // the function pointers are only valid as long as the library is loaded
+  unsafe { mem::forget(lib); }
+  Ok(table)
+}
+
+/// The loaded function table. The stateless functions like
+/// `Ovr::matrix_projection` go through this as well, and load the library
+/// on first use if `Ovr::initialize` has not been called. Panics if the
+/// runtime is not available, which cannot happen after a successful
+/// `Ovr::try_initialize`.
+pub fn functions() -> &'static FunctionTable {
+  match load() {
+    Ok(table) => table,
+    Err(e)    => panic!("libovr is not available: {}", e),
+  }
+}
@@ -0,0 +1,234 @@
+//! Export of tracking samples to CSV and JSON Lines for offline analysis.
+//!
+//! Both formats flatten a `TrackingState` into the same named columns, with
+//! the `StatusFlags` bits as separate boolean columns. Samples can come from
+//! any iterator (see `export_all`), e.g. a `recording::RecordingReader`, or
+//! from polling a live HMD with `recording::record_session`.
+
+use std::default::Default;
+use std::io::IoResult;
+use std::num::Float;
+
+use ffi;
+use ffi::{TrackingState, Posef, Vector3f};
+use recording::SampleSink;
+
+/// How orientations are written.
+#[deriving(Clone, PartialEq, Show)]
+pub enum Orientation {
+  /// Quaternion components `x`, `y`, `z` and `w`.
+  OrientationQuaternion,
+  /// Yaw, pitch and roll in radians, see `Quatf::to_euler_yxz`.
+  OrientationEuler,
+}
+
+#[deriving(Clone, PartialEq, Show)]
+enum Value {
+  Float(f64),
+  Int(u32),
+  Bool(bool),
+}
+
+/// The `StatusFlags` bits and their column names.
+static STATUS_FLAGS: [(&'static str, u32), ..5] = [
+  ("orientation_tracked", ffi::Status_OrientationTracked),
+  ("position_tracked", ffi::Status_PositionTracked),
+  ("camera_pose_tracked", ffi::Status_CameraPoseTracked),
+  ("position_connected", ffi::Status_PositionConnected),
+  ("hmd_connected", ffi::Status_HmdConnected),
+];
+
+struct Columns {
+  orientation: Orientation,
+  columns: Vec<(String, Value)>,
+}
+
+impl Columns {
+
+  fn float(&mut self, name: &str, v: f64) {
+    self.columns.push((name.to_string(), Float(v)));
+  }
+
+  fn vector(&mut self, prefix: &str, v: &Vector3f) {
+    self.float(format!("{}_x", prefix).as_slice(), v.x as f64);
+    self.float(format!("{}_y", prefix).as_slice(), v.y as f64);
+    self.float(format!("{}_z", prefix).as_slice(), v.z as f64);
+  }
+
+  fn pose(&mut self, prefix: &str, pose: &Posef) {
+    let q = &pose.Orientation;
+    match self.orientation {
+      OrientationQuaternion => {
+        self.float(format!("{}_orientation_x", prefix).as_slice(), q.x as f64);
+        self.float(format!("{}_orientation_y", prefix).as_slice(), q.y as f64);
+        self.float(format!("{}_orientation_z", prefix).as_slice(), q.z as f64);
+        self.float(format!("{}_orientation_w", prefix).as_slice(), q.w as f64);
+      }
+      OrientationEuler => {
+        let (yaw, pitch, roll) = q.to_euler_yxz();
+        self.float(format!("{}_yaw", prefix).as_slice(), yaw as f64);
+        self.float(format!("{}_pitch", prefix).as_slice(), pitch as f64);
+        self.float(format!("{}_roll", prefix).as_slice(), roll as f64);
+      }
+    }
+    self.vector(format!("{}_position", prefix).as_slice(), &pose.Position);
+  }
+}
+
+/// Flattens `state` into named values. The names only depend on `orientation`.
+fn flatten(state: &TrackingState, orientation: Orientation) -> Vec<(String, Value)> {
+  let mut c = Columns{ orientation: orientation, columns: Vec::new() };
+  let head = &state.HeadPose;
+  c.float("time", head.TimeInSeconds);
+  c.pose("head", &head.ThePose);
+  c.vector("head_angular_velocity", &head.AngularVelocity);
+  c.vector("head_linear_velocity", &head.LinearVelocity);
+  c.vector("head_angular_acceleration", &head.AngularAcceleration);
+  c.vector("head_linear_acceleration", &head.LinearAcceleration);
+  c.pose("camera", &state.CameraPose);
+  c.pose("leveled_camera", &state.LeveledCameraPose);
+
+  let raw = &state.RawSensorData;
+  c.vector("accelerometer", &raw.Accelerometer);
+  c.vector("gyro", &raw.Gyro);
+  c.vector("magnetometer", &raw.Magnetometer);
+  c.float("temperature", raw.Temperature as f64);
+  c.float("sensor_time", raw.TimeInSeconds as f64);
+
+  for &(name, bit) in STATUS_FLAGS.iter() {
+    c.columns.push((name.to_string(), Bool(state.StatusFlags & bit != 0)));
+  }
+  c.float("last_vision_processing_time", state.LastVisionProcessingTime);
+  c.float("last_vision_frame_latency", state.LastVisionFrameLatency);
+  c.columns.push(("last_camera_frame_counter".to_string(), Int(state.LastCameraFrameCounter)));
+  c.columns
+}
+
+/// Returns the column names used for the given orientation format.
+pub fn column_names(orientation: Orientation) -> Vec<String> {
+  let state: TrackingState = Default::default();
+  flatten(&state, orientation).into_iter().map(|(name, _)| name).collect()
+}
+
+//-----------------------------------------------------------------------------------
+// CSV
+//-----------------------------------------------------------------------------------
+
+/// Writes one comma separated row per sample after a header row with the
+/// column names. Booleans are written as `1` and `0`, non-finite numbers as
+/// empty cells.
+pub struct CsvExporter<W> {
+  writer: W,
+  orientation: Orientation,
+  samples: uint,
+}
+
+impl<W: Writer> CsvExporter<W> {
+
+  /// Writes the header row and returns the exporter.
+  pub fn new(mut writer: W, orientation: Orientation) -> IoResult<CsvExporter<W>> {
+    let names = column_names(orientation);
+    let header: Vec<&str> = names.iter().map(|n| n.as_slice()).collect();
+    try!(writer.write_line(header.connect(",").as_slice()));
+    Ok(CsvExporter{ writer: writer, orientation: orientation, samples: 0 })
+  }
+
+  pub fn write_sample(&mut self, state: &TrackingState) -> IoResult<()> {
+    let cells: Vec<String> = flatten(state, self.orientation).into_iter().map(|(_, v)| {
+      match v {
+        Float(f) if f.is_finite() => format!("{}", f),
+        Float(_)                  => String::new(),
+        Int(i)                    => format!("{}", i),
+        Bool(b)                   => (if b { "1" } else { "0" }).to_string(),
+      }
+    }).collect();
+    try!(self.writer.write_line(cells.connect(",").as_slice()));
+    self.samples += 1;
+    Ok(())
+  }
+
+  /// Number of samples written so far.
+  pub fn samples(&self) -> uint {
+    self.samples
+  }
+
+  pub fn flush(&mut self) -> IoResult<()> {
+    self.writer.flush()
+  }
+
+  pub fn into_inner(self) -> W {
+    self.writer
+  }
+}
+
+impl<W: Writer> SampleSink for CsvExporter<W> {
+  fn write_sample(&mut self, state: &TrackingState) -> IoResult<()> { self.write_sample(state) }
+  fn flush(&mut self) -> IoResult<()> { self.flush() }
+}
+
+//-----------------------------------------------------------------------------------
+// JSON Lines
+//-----------------------------------------------------------------------------------
+
+/// Writes one flat JSON object per line and sample, with the same keys as
+/// the CSV columns. Non-finite numbers are written as `null`.
+pub struct JsonLinesExporter<W> {
+  writer: W,
+  orientation: Orientation,
+  samples: uint,
+}
+
+impl<W: Writer> JsonLinesExporter<W> {
+
+  pub fn new(writer: W, orientation: Orientation) -> JsonLinesExporter<W> {
+    JsonLinesExporter{ writer: writer, orientation: orientation, samples: 0 }
+  }
+
+  pub fn write_sample(&mut self, state: &TrackingState) -> IoResult<()> {
+    let fields: Vec<String> = flatten(state, self.orientation).into_iter().map(|(name, v)| {
+      let value = match v {
+        Float(f) if f.is_finite() => format!("{}", f),
+        Float(_)                  => "null".to_string(),
+        Int(i)                    => format!("{}", i),
+        Bool(b)                   => format!("{}", b),
+      };
+      // column names are plain identifiers and need no escaping
+      format!("\"{}\":{}", name, value)
+    }).collect();
+    try!(self.writer.write_str("{"));
+    try!(self.writer.write_str(fields.connect(",").as_slice()));
+    try!(self.writer.write_str("}\n"));
+    self.samples += 1;
+    Ok(())
+  }
+
+  /// Number of samples written so far.
+  pub fn samples(&self) -> uint {
+    self.samples
+  }
+
+  pub fn flush(&mut self) -> IoResult<()> {
+    self.writer.flush()
+  }
+
+  pub fn into_inner(self) -> W {
+    self.writer
+  }
+}
+
+impl<W: Writer> SampleSink for JsonLinesExporter<W> {
+  fn write_sample(&mut self, state: &TrackingState) -> IoResult<()> { self.write_sample(state) }
+  fn flush(&mut self) -> IoResult<()> { self.flush() }
+}
+
+/// Writes every sample of `samples` to `sink` and flushes it. Returns the
+/// number of samples written.
+pub fn export_all<I: Iterator<TrackingState>, S: SampleSink>(samples: I, sink: &mut S) -> IoResult<uint> {
+  let mut written = 0u;
+  for state in samples {
+    try!(sink.write_sample(&state));
+    written += 1;
+  }
+  try!(sink.flush());
+  Ok(written)
+}
@@ -1,10 +1,13 @@
 #![allow(non_snake_case)]
 #![allow(non_camel_case_types)]
 
-use libc::{c_uint, c_int, c_float, c_char, c_uchar, c_void, c_double, c_short, uint32_t, uintptr_t};
+use libc::{c_uint, c_int, c_float, c_char, c_uchar, c_void, c_double, c_short, c_ushort, uint32_t, uintptr_t};
 use std::ptr;
 use std::default::Default;
 use std::num::FromPrimitive;
+use std::mem;
+#[cfg(feature = "dynamic")]
+use std::dynamic_lib::DynamicLibrary;
 
 // plain typedefs
 pub type OvrBool = c_char;
@@ -16,6 +19,8 @@ pub type OvrBool = c_char;
 // ***** Simple Math Structures
 
 /// A 2D vector with integer components.
+#[cfg_attr(feature = "serde", deriving_serialize)]
+#[cfg_attr(feature = "serde", deriving_deserialize)]
 #[deriving(Clone, Default, Show)]
 #[repr(C)]
 pub struct Vector2i {
@@ -24,6 +29,8 @@ pub struct Vector2i {
 }
 
 /// A 2D size with integer components.
+#[cfg_attr(feature = "serde", deriving_serialize)]
+#[cfg_attr(feature = "serde", deriving_deserialize)]
 #[deriving(Clone, Default, Show)]
 #[repr(C)]
 pub struct Sizei {
@@ -33,6 +40,8 @@ pub struct Sizei {
 
 /// A 2D rectangle with a position and size.
 /// All components are integers.
+#[cfg_attr(feature = "serde", deriving_serialize)]
+#[cfg_attr(feature = "serde", deriving_deserialize)]
 #[deriving(Clone, Default, Show)]
 #[repr(C)]
 pub struct Recti {
@@ -41,6 +50,8 @@ pub struct Recti {
 }
 
 /// A quaternion rotation.
+#[cfg_attr(feature = "serde", deriving_serialize)]
+#[cfg_attr(feature = "serde", deriving_deserialize)]
 #[deriving(Clone, Default, Show)]
 #[repr(C)]
 pub struct Quatf {
@@ -51,6 +62,8 @@ pub struct Quatf {
 }
 
 /// A 2D vector with float components.
+#[cfg_attr(feature = "serde", deriving_serialize)]
+#[cfg_attr(feature = "serde", deriving_deserialize)]
 #[deriving(Clone, Default, Show)]
 #[repr(C)]
 pub struct Vector2f {
@@ -59,6 +72,8 @@ pub struct Vector2f {
 }
 
 /// A 3D vector with float components.
+#[cfg_attr(feature = "serde", deriving_serialize)]
+#[cfg_attr(feature = "serde", deriving_deserialize)]
 #[deriving(Clone, Default, Show)]
 #[repr(C)]
 pub struct Vector3f {
@@ -75,6 +90,8 @@ pub struct Matrix4f {
 }
 
 /// Position and orientation together.
+#[cfg_attr(feature = "serde", deriving_serialize)]
+#[cfg_attr(feature = "serde", deriving_deserialize)]
 #[deriving(Clone, Default, Show)]
 #[repr(C)]
 pub struct Posef {
@@ -83,6 +100,8 @@ pub struct Posef {
 }
 
 /// A full pose (rigid body) configuration with first and second derivatives.
+#[cfg_attr(feature = "serde", deriving_serialize)]
+#[cfg_attr(feature = "serde", deriving_deserialize)]
 #[deriving(Clone, Default, Show)]
 #[repr(C)]
 pub struct PoseStatef {
@@ -97,6 +116,8 @@ pub struct PoseStatef {
 /// Field Of View (FOV) in tangent of the angle units.
 /// As an example, for a standard 90 degree vertical FOV, we would
 /// have: { UpTan = tan(90 degrees / 2), DownTan = tan(90 degrees / 2) }.
+#[cfg_attr(feature = "serde", deriving_serialize)]
+#[cfg_attr(feature = "serde", deriving_deserialize)]
 #[deriving(Clone, Default, Show)]
 #[repr(C)]
 pub struct FovPort {
@@ -226,6 +247,21 @@ pub struct HmdDesc {
 
 pub enum Hmd {}
 
+/// Property keys for ovrHmd_GetFloat and friends, and their defaults (OVR_CAPI_Keys.h).
+pub const KEY_USER                         : &'static str = "User";
+pub const KEY_NAME                         : &'static str = "Name";
+pub const KEY_GENDER                       : &'static str = "Gender";
+pub const KEY_PLAYER_HEIGHT                : &'static str = "PlayerHeight";
+pub const KEY_EYE_HEIGHT                   : &'static str = "EyeHeight";
+pub const KEY_IPD                          : &'static str = "IPD";
+pub const KEY_NECK_TO_EYE_DISTANCE         : &'static str = "NeckEyeDistance";
+
+pub const DEFAULT_PLAYER_HEIGHT            : c_float = 1.778;
+pub const DEFAULT_EYE_HEIGHT               : c_float = 1.675;
+pub const DEFAULT_IPD                      : c_float = 0.064;
+pub const DEFAULT_NECK_TO_EYE_HORIZONTAL   : c_float = 0.0805;
+pub const DEFAULT_NECK_TO_EYE_VERTICAL     : c_float = 0.075;
+
 /// Bit flags describing the current status of sensor tracking.
 pub const Status_OrientationTracked        : c_uint = 0x0001;
 pub const Status_PositionTracked           : c_uint = 0x0002;
@@ -234,6 +270,8 @@ pub const Status_PositionConnected         : c_uint = 0x0020;
 pub const Status_HmdConnected              : c_uint = 0x0080;
 
 /// Specifies a reading we can query from the sensor.
+#[cfg_attr(feature = "serde", deriving_serialize)]
+#[cfg_attr(feature = "serde", deriving_deserialize)]
 #[deriving(Clone, Default, Show)]
 #[repr(C)]
 pub struct SensorData {
@@ -247,6 +285,8 @@ pub struct SensorData {
 
 /// Tracking state at a given absolute time (describes predicted HMD pose etc).
 /// Returned by ovrHmd_GetTrackingState.
+#[cfg_attr(feature = "serde", deriving_serialize)]
+#[cfg_attr(feature = "serde", deriving_deserialize)]
 #[deriving(Clone, Default, Show)]
 #[repr(C)]
 pub struct TrackingState {
@@ -262,6 +302,8 @@ pub struct TrackingState {
 
 
 /// Frame timing data reported by ovrHmd_BeginFrameTiming() or ovrHmd_BeginFrame().
+#[cfg_attr(feature = "serde", deriving_serialize)]
+#[cfg_attr(feature = "serde", deriving_deserialize)]
 #[repr(C)]
 pub struct FrameTiming {
   pub DeltaSeconds: c_float,
@@ -277,6 +319,8 @@ pub struct FrameTiming {
 /// is not included here as it can be specified separately and modified per frame through:
 ///    (a) ovrHmd_GetRenderScaleAndOffset in the case of client rendered distortion,
 /// or (b) passing different values via ovrTexture in the case of SDK rendered distortion.
+#[cfg_attr(feature = "serde", deriving_serialize)]
+#[cfg_attr(feature = "serde", deriving_deserialize)]
 #[deriving(Clone, Default, Show)]
 #[repr(C)]
 pub struct EyeRenderDesc {
@@ -333,6 +377,31 @@ pub struct Texture {
   pub PlatformData: [uintptr_t, ..8],
 }
 
+/// Describes a vertex used by the distortion mesh. This is intended to be converted into
+/// the engine-specific format. Some fields may be unused based on the ovrDistortionCaps
+/// flags selected. TexG and TexB, for example, are not used if chromatic correction is
+/// not requested.
+#[deriving(Clone, Default, Show)]
+#[repr(C)]
+pub struct DistortionVertex {
+  pub ScreenPosNDC: Vector2f,
+  pub TimeWarpFactor: c_float,
+  pub VignetteFactor: c_float,
+  pub TanEyeAnglesR: Vector2f,
+  pub TanEyeAnglesG: Vector2f,
+  pub TanEyeAnglesB: Vector2f,
+}
+
+/// Describes a full set of distortion mesh data, filled in by ovrHmd_CreateDistortionMesh.
+/// Contents of this data structure, if not null, should be freed by ovrHmd_DestroyDistortionMesh.
+#[repr(C)]
+pub struct DistortionMesh {
+  pub pVertexData: *mut DistortionVertex,
+  pub pIndexData: *mut c_ushort,
+  pub VertexCount: c_uint,
+  pub IndexCount: c_uint,
+}
+
 /// Used by ovrhmd_GetHSWDisplayState to report the current display state.
 #[deriving(Clone, Default, Show)]
 #[repr(C)]
@@ -344,7 +413,46 @@ pub struct HSWDisplayState {
 
 
 
-extern "C" {
+/// Declares the SDK functions. Normally they are linked directly. With the
+/// `dynamic` feature every function instead becomes a field of `FunctionTable`,
+/// which is resolved from the shared library at runtime (see `dynamic`), and
+/// a wrapper with the same signature calls through the table.
+macro_rules! ovr_functions(
+  ($(pub fn $name:ident($($arg:ident: $ty:ty),*) $(-> $ret:ty)*;)*) => (
+
+    #[cfg(not(feature = "dynamic"))]
+    extern "C" {
+      $(pub fn $name($($arg: $ty),*) $(-> $ret)*;)*
+    }
+
+    /// Entry points of libovr, resolved at runtime.
+    #[cfg(feature = "dynamic")]
+    pub struct FunctionTable {
+      $(pub $name: extern "C" fn($($ty),*) $(-> $ret)*,)*
+    }
+
+    #[cfg(feature = "dynamic")]
+    impl FunctionTable {
+      /// Looks up every function, failing on the first missing symbol.
+      pub fn load(lib: &DynamicLibrary) -> Result<FunctionTable, String> {
+        unsafe {
+          Ok(FunctionTable{
+            $($name: mem::transmute(try!(lib.symbol::<u8>(stringify!($name)))),)*
+          })
+        }
+      }
+    }
+
+    $(
+      #[cfg(feature = "dynamic")]
+      pub unsafe fn $name($($arg: $ty),*) $(-> $ret)* {
+        (::dynamic::functions().$name)($($arg),*)
+      }
+    )*
+  )
+)
+
+ovr_functions! {
   pub fn ovr_InitializeRenderingShim();
   pub fn ovr_Initialize() -> OvrBool;
   pub fn ovr_Shutdown();
@@ -353,7 +461,7 @@ extern "C" {
   pub fn ovrHmd_Create(index: c_int) -> *mut Hmd;
   pub fn ovrHmd_Destroy(hmd: *mut Hmd);
   pub fn ovrHmd_CreateDebug(hmd_type: c_uint) -> *mut Hmd;
-  pub fn ovrHmd_GetLastError(hmd: *mut Hmd) -> *const char;
+  pub fn ovrHmd_GetLastError(hmd: *mut Hmd) -> *const c_char;
   pub fn ovrHmd_AttachToWindow(hmd: *mut Hmd, 
                                window: *mut c_void, 
                                destMirrorRect: *const Recti, 
@@ -383,14 +491,25 @@ extern "C" {
                             hmdToEyeViewOffset: *const Vector3f, // is not specified as const in the header, but should be?
                             outEyePoses: *mut Posef, 
                             outHmdTrackingState: *mut TrackingState);
+  pub fn ovrHmd_GetHmdPosePerEye(hmd: *mut Hmd, eye: c_uint) -> Posef;
   pub fn ovrHmd_GetRenderDesc(hmd: *mut Hmd, eyeType: c_uint, fov: FovPort) -> EyeRenderDesc;
   
-  // TODO: Distortion mesh stuff omitted for the time being...
-  
+  pub fn ovrHmd_CreateDistortionMesh(hmd: *mut Hmd,
+                                     eyeType: c_uint,
+                                     fov: FovPort,
+                                     distortionCaps: c_uint,
+                                     meshData: *mut DistortionMesh) -> OvrBool;
+  pub fn ovrHmd_DestroyDistortionMesh(meshData: *mut DistortionMesh);
+  pub fn ovrHmd_GetRenderScaleAndOffset(fov: FovPort,
+                                        textureSize: Sizei,
+                                        renderViewport: Recti,
+                                        uvScaleOffsetOut: *mut Vector2f);
+
   pub fn ovrHmd_GetFrameTiming(hmd: *mut Hmd, frameIndex: c_uint) -> FrameTiming;
   pub fn ovrHmd_BeginFrameTiming(hmd: *mut Hmd, frameIndex: c_uint) -> FrameTiming;
   pub fn ovrHmd_EndFrameTiming(hmd: *mut Hmd);
   pub fn ovrHmd_ResetFrameTiming(hmd: *mut Hmd, frameIndex: c_uint);
+  // twmOut points to two matrices, for the start and the end of scanout
   pub fn ovrHmd_GetEyeTimewarpMatrices(hmd: *mut Hmd, eye: c_uint, renderPose: Posef, twmOut: *mut Matrix4f);
   
 //-------------------------------------------------------------------------------------
@@ -407,9 +526,9 @@ extern "C" {
 // -----------------------------------------------------------------------------------
 // ***** Latency Test interface
 
-  pub fn ovrHmd_ProcessLatencyTest(hmd: *mut Hmd, rgbColorOut: *const c_uchar) -> OvrBool;  
+  pub fn ovrHmd_ProcessLatencyTest(hmd: *mut Hmd, rgbColorOut: *mut c_uchar) -> OvrBool;
   pub fn ovrHmd_GetLatencyTestResult(hmd: *mut Hmd) -> *const c_char;
-  pub fn ovrHmd_GetLatencyTest2DrawColor(hmd: *mut Hmd, rgbColorOut: &mut [c_uchar, ..3]);
+  pub fn ovrHmd_GetLatencyTest2DrawColor(hmd: *mut Hmd, rgbColorOut: *mut c_uchar) -> OvrBool;
   
 //-------------------------------------------------------------------------------------
 // ***** Health and Safety Warning Display interface
@@ -418,88 +537,30 @@ extern "C" {
   pub fn ovrHmd_DismissHSWDisplay(hmd: *mut Hmd) -> OvrBool;
   
 
-  pub fn ovrHmd_GetBool(hmd: *mut Hmd, propertyName: *const char, defaultVal: OvrBool) -> OvrBool;
-  pub fn ovrHmd_SetBool(hmd: *mut Hmd, propertyName: *const char, value: OvrBool) -> OvrBool;
+  pub fn ovrHmd_GetBool(hmd: *mut Hmd, propertyName: *const c_char, defaultVal: OvrBool) -> OvrBool;
+  pub fn ovrHmd_SetBool(hmd: *mut Hmd, propertyName: *const c_char, value: OvrBool) -> OvrBool;
 
-  pub fn ovrHmd_GetInt(hmd: *mut Hmd, propertyName: *const char, defaultVal: c_int) -> c_int;
-  pub fn ovrHmd_SetInt(hmd: *mut Hmd, propertyName: *const char, value: c_int) -> OvrBool;  
+  pub fn ovrHmd_GetInt(hmd: *mut Hmd, propertyName: *const c_char, defaultVal: c_int) -> c_int;
+  pub fn ovrHmd_SetInt(hmd: *mut Hmd, propertyName: *const c_char, value: c_int) -> OvrBool;  
 
-  pub fn ovrHmd_GetFloat(hmd: *mut Hmd, propertyName: *const char, defaultVal: c_float) -> c_float;
-  pub fn ovrHmd_SetFloat(hmd: *mut Hmd, propertyName: *const char, value: c_float) -> OvrBool;  
+  pub fn ovrHmd_GetFloat(hmd: *mut Hmd, propertyName: *const c_char, defaultVal: c_float) -> c_float;
+  pub fn ovrHmd_SetFloat(hmd: *mut Hmd, propertyName: *const c_char, value: c_float) -> OvrBool;  
   
-  pub fn ovrHmd_GetFloatArray(hmd: *mut Hmd, propertyName: *const char, values: *mut c_float, arraySize: c_uint) -> c_uint;
-  pub fn ovrHmd_SetFloatArray(hmd: *mut Hmd, propertyName: *const char, values: *mut c_float, arraySize: c_uint) -> OvrBool;
+  pub fn ovrHmd_GetFloatArray(hmd: *mut Hmd, propertyName: *const c_char, values: *mut c_float, arraySize: c_uint) -> c_uint;
+  pub fn ovrHmd_SetFloatArray(hmd: *mut Hmd, propertyName: *const c_char, values: *mut c_float, arraySize: c_uint) -> OvrBool;
 
-  pub fn ovrHmd_GetString(hmd: *mut Hmd, propertyName: *const char, defaultVal: *const char) -> *const char;
-  pub fn ovrHmd_SetString(hmd: *mut Hmd, propertyName: *const char, value: *const char) -> OvrBool;
+  pub fn ovrHmd_GetString(hmd: *mut Hmd, propertyName: *const c_char, defaultVal: *const c_char) -> *const c_char;
+  pub fn ovrHmd_SetString(hmd: *mut Hmd, propertyName: *const c_char, value: *const c_char) -> OvrBool;
   
 // -----------------------------------------------------------------------------------
 // ***** Logging  
-  pub fn ovrHmd_StartPerfLog(hmd: *mut Hmd, fileName: *const char, userData1: *const char) -> OvrBool;
+  pub fn ovrHmd_StartPerfLog(hmd: *mut Hmd, fileName: *const c_char, userData1: *const c_char) -> OvrBool;
   pub fn ovrHmd_StopPerfLog(hmd: *mut Hmd) -> OvrBool;
   
 }
 
 
 
-/*
-
-extern "C" {
-    pub fn ovr_Initialize() -> bool;
-    pub fn ovr_Shutdown();
-    pub fn ovrHmd_Detect() -> c_int;
-    pub fn ovrHmd_Create(index: c_int) -> *mut Hmd;
-    pub fn ovrHmd_Destroy(hmd: *mut Hmd);
-    pub fn ovrHmd_CreateDebug(hmd_type: c_int) -> *mut Hmd;
-    pub fn ovrHmd_GetLastError(hmd: *mut Hmd) -> *const c_char;
-    pub fn ovrHmd_GetEnabledCaps(hmd: *mut Hmd) -> c_uint;
-    pub fn ovrHmd_SetEnabledCaps(hmd: *mut Hmd, flags: c_uint);
-    pub fn ovrHmd_StartSensor(hmd: *mut Hmd,
-                              supported: c_uint,
-                              required: c_uint) -> bool;
-    pub fn ovrHmd_StopSensor(hmd: *mut Hmd);
-    pub fn ovrHmd_ResetSensor(hmd: *mut Hmd);
-    pub fn ovrHmd_GetSensorState(hmd: *mut Hmd,
-                                 abs_time: c_double) -> SensorState;
-    pub fn ovrHmd_GetSensorDesc(hmd: *mut Hmd,
-                                sensor_desc: *mut SensorDesc) -> bool;
-    pub fn ovrHmd_GetDesc(hmd: *mut Hmd,
-                          size: *mut HmdDesc);
-    pub fn ovrHmd_GetFovTextureSize(hmd: *mut Hmd,
-                                    eye: c_uint,
-                                    fov: FovPort,
-                                    pixels: c_float) -> Sizei;
-    pub fn ovrHmd_ConfigureRendering(hmd: *mut Hmd,
-                                     apiConfig: *const RenderApiConfig,
-                                     distortionCaps: c_uint,
-                                     fov_in: *const FovPort,
-                                     render_desc_out: *mut EyeRenderDesc) -> bool;
-    pub fn ovrHmd_BeginFrame(hmd: *mut Hmd,
-                             frame_index: c_uint) -> FrameTiming;
-    pub fn ovrHmd_EndFrame(hmd: *mut Hmd);
-    pub fn ovrHmd_BeginEyeRender(hmd: *mut Hmd, eye: c_uint) -> Posef;
-    pub fn ovrHmd_EndEyeRender(hmd: *mut Hmd, eye: c_uint, 
-                               pose: Posef, texture: *const Texture);
-    pub fn ovrMatrix4f_Projection(fov: FovPort,
-                                  znear: c_float,
-                                  zfar: c_float,
-                                  right_handed: bool) -> Matrix4f;
-
-    pub fn ovr_WaitTillTime(abs_time: c_double) -> c_double;
-    pub fn ovr_GetTimeInSeconds() -> c_double;
-}
-*/
-
-
-
-
-
-
-
-
-
-
-
 
 /*
 /// Experiments with true enums to wrap the C enums, see:
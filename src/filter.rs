@@ -0,0 +1,326 @@
+//! Smoothing filters for streams of tracking samples.
+//!
+//! A `TrackingFilter` is fed one `TrackingState` at a time, in order of
+//! `HeadPose.TimeInSeconds`, and returns the state with a filtered head pose.
+//! All other fields, including the velocities, are passed through. Samples
+//! may come from a live `Hmd` or any sequence, see `Filtered`. Orientations
+//! are filtered on the unit quaternion manifold, by interpolating with slerp
+//! and measuring rotations as rotation vectors.
+//!
+//! Filters can be combined with a `FilterChain`, e.g. a `HoldAndBlend`
+//! followed by a `OneEuroFilter`.
+
+use std::num::Float;
+use std::f64::consts::PI;
+
+use ffi;
+use ffi::{TrackingState, Vector3f, Quatf};
+
+pub trait TrackingFilter {
+  fn filter(&mut self, state: &TrackingState) -> TrackingState;
+
+  /// Forgets all history, the next sample passes through unchanged.
+  fn reset(&mut self);
+}
+
+/// Time of the sample and the previous filtered head pose.
+#[deriving(Clone, Show)]
+struct Last {
+  time: f64,
+  position: Vector3f,
+  orientation: Quatf,
+}
+
+fn last_of(state: &TrackingState) -> Last {
+  Last{
+    time: state.HeadPose.TimeInSeconds,
+    position: state.HeadPose.ThePose.Position.clone(),
+    orientation: state.HeadPose.ThePose.Orientation.clone(),
+  }
+}
+
+fn with_pose(state: &TrackingState, last: &Last) -> TrackingState {
+  let mut result = state.clone();
+  result.HeadPose.ThePose.Position = last.position.clone();
+  result.HeadPose.ThePose.Orientation = last.orientation.clone();
+  result
+}
+
+//-----------------------------------------------------------------------------------
+// Exponential smoothing
+//-----------------------------------------------------------------------------------
+
+/// First order low pass filter. The time constants are in seconds, so the
+/// amount of smoothing does not depend on the sample rate. A time constant of
+/// zero disables the filter for that component.
+pub struct ExponentialFilter {
+  pub position_time_constant: f64,
+  pub orientation_time_constant: f64,
+  last: Option<Last>,
+}
+
+impl ExponentialFilter {
+
+  pub fn new(position_time_constant: f64, orientation_time_constant: f64) -> ExponentialFilter {
+    ExponentialFilter{
+      position_time_constant: position_time_constant,
+      orientation_time_constant: orientation_time_constant,
+      last: None,
+    }
+  }
+}
+
+fn exponential_alpha(time_constant: f64, dt: f64) -> f32 {
+  if time_constant <= 0.0 { 1.0 } else { (1.0 - (-dt / time_constant).exp()) as f32 }
+}
+
+impl TrackingFilter for ExponentialFilter {
+
+  fn filter(&mut self, state: &TrackingState) -> TrackingState {
+    let next = match self.last {
+      None => last_of(state),
+      Some(ref last) => {
+        let dt = (state.HeadPose.TimeInSeconds - last.time).max(0.0);
+        let pose = &state.HeadPose.ThePose;
+        Last{
+          time: state.HeadPose.TimeInSeconds,
+          position: last.position.lerp(&pose.Position, exponential_alpha(self.position_time_constant, dt)),
+          orientation: last.orientation.slerp(&pose.Orientation, exponential_alpha(self.orientation_time_constant, dt)),
+        }
+      }
+    };
+    let result = with_pose(state, &next);
+    self.last = Some(next);
+    result
+  }
+
+  fn reset(&mut self) {
+    self.last = None;
+  }
+}
+
+//-----------------------------------------------------------------------------------
+// One euro filter
+//-----------------------------------------------------------------------------------
+
+/// The 1€ filter by Casiez et al.: a low pass filter whose cutoff frequency
+/// rises with the speed of the signal, so it removes jitter while the head is
+/// still and adds little lag while it moves.
+#[deriving(Clone, Show)]
+pub struct OneEuroParams {
+  /// Cutoff frequency in Hz at zero speed.
+  pub min_cutoff: f64,
+  /// Increase of the cutoff frequency per unit of speed (m/s or rad/s).
+  pub beta: f64,
+  /// Cutoff frequency in Hz for the speed estimate.
+  pub derivative_cutoff: f64,
+}
+
+pub struct OneEuroFilter {
+  pub position: OneEuroParams,
+  pub orientation: OneEuroParams,
+  last: Option<Last>,
+  linear_speed: f64,
+  angular_speed: f64,
+}
+
+impl OneEuroFilter {
+
+  pub fn new(position: OneEuroParams, orientation: OneEuroParams) -> OneEuroFilter {
+    OneEuroFilter{ position: position, orientation: orientation, last: None, linear_speed: 0.0, angular_speed: 0.0 }
+  }
+}
+
+fn one_euro_alpha(cutoff: f64, dt: f64) -> f64 {
+  let tau = 1.0 / (2.0 * PI * cutoff);
+  1.0 / (1.0 + tau / dt)
+}
+
+/// Updates the smoothed `speed` with the raw speed and returns the weight of
+/// the new sample.
+fn one_euro_step(params: &OneEuroParams, speed: &mut f64, raw_speed: f64, dt: f64) -> f32 {
+  let a = one_euro_alpha(params.derivative_cutoff, dt);
+  *speed = *speed + a * (raw_speed - *speed);
+  one_euro_alpha(params.min_cutoff + params.beta * *speed, dt) as f32
+}
+
+impl TrackingFilter for OneEuroFilter {
+
+  fn filter(&mut self, state: &TrackingState) -> TrackingState {
+    let next = match self.last {
+      None => last_of(state),
+      Some(ref last) => {
+        let dt = state.HeadPose.TimeInSeconds - last.time;
+        if dt <= 0.0 {
+          // a repeated sample, keep the previous output
+          last.clone()
+        } else {
+          let pose = &state.HeadPose.ThePose;
+          let moved = pose.Position.sub(&last.position).length() as f64;
+          let turned = last.orientation.conjugate().mul(&pose.Orientation).to_rotation_vector().length() as f64;
+          let a_position = one_euro_step(&self.position, &mut self.linear_speed, moved / dt, dt);
+          let a_orientation = one_euro_step(&self.orientation, &mut self.angular_speed, turned / dt, dt);
+          Last{
+            time: state.HeadPose.TimeInSeconds,
+            position: last.position.lerp(&pose.Position, a_position),
+            orientation: last.orientation.slerp(&pose.Orientation, a_orientation),
+          }
+        }
+      }
+    };
+    let result = with_pose(state, &next);
+    self.last = Some(next);
+    result
+  }
+
+  fn reset(&mut self) {
+    self.last = None;
+    self.linear_speed = 0.0;
+    self.angular_speed = 0.0;
+  }
+}
+
+//-----------------------------------------------------------------------------------
+// Hold and blend
+//-----------------------------------------------------------------------------------
+
+/// Freezes the head position while `Status_PositionTracked` is not set, and
+/// blends back to the tracked position over `blend_time` seconds once it is
+/// set again. This hides the jumps when the head moves in and out of the
+/// camera frustum. Orientation is passed through.
+pub struct HoldAndBlend {
+  pub blend_time: f64,
+  /// The last position output while tracked, if any.
+  held: Option<Vector3f>,
+  tracked: bool,
+  /// Offset from the tracked position at the moment tracking was regained,
+  /// and that moment.
+  blend: Option<(Vector3f, f64)>,
+}
+
+impl HoldAndBlend {
+
+  pub fn new(blend_time: f64) -> HoldAndBlend {
+    HoldAndBlend{ blend_time: blend_time, held: None, tracked: true, blend: None }
+  }
+
+  /// Whether the position is currently held.
+  pub fn is_holding(&self) -> bool {
+    !self.tracked && self.held.is_some()
+  }
+}
+
+impl TrackingFilter for HoldAndBlend {
+
+  fn filter(&mut self, state: &TrackingState) -> TrackingState {
+    let mut result = state.clone();
+    let time = state.HeadPose.TimeInSeconds;
+    let position = state.HeadPose.ThePose.Position.clone();
+    let tracked = state.StatusFlags & ffi::Status_PositionTracked != 0;
+
+    if !tracked {
+      self.tracked = false;
+      self.blend = None;
+      match self.held {
+        Some(ref held) => result.HeadPose.ThePose.Position = held.clone(),
+        None => {}
+      }
+      return result;
+    }
+
+    if !self.tracked {
+      self.tracked = true;
+      self.blend = match self.held {
+        Some(ref held) if self.blend_time > 0.0 => Some((held.sub(&position), time)),
+        _ => None,
+      };
+    }
+
+    let output = match self.blend {
+      Some((ref offset, start)) if time - start < self.blend_time => {
+        let remaining = 1.0 - ((time - start).max(0.0) / self.blend_time) as f32;
+        Some(position.add(&offset.scale(remaining)))
+      }
+      _ => None,
+    };
+    let output = match output {
+      Some(p) => p,
+      None => {
+        self.blend = None;
+        position
+      }
+    };
+    result.HeadPose.ThePose.Position = output.clone();
+    self.held = Some(output);
+    result
+  }
+
+  fn reset(&mut self) {
+    self.held = None;
+    self.tracked = true;
+    self.blend = None;
+  }
+}
+
+//-----------------------------------------------------------------------------------
+// Combinators
+//-----------------------------------------------------------------------------------
+
+/// Runs samples through several filters in order.
+pub struct FilterChain {
+  pub stages: Vec<Box<TrackingFilter + 'static>>,
+}
+
+impl FilterChain {
+
+  pub fn new() -> FilterChain {
+    FilterChain{ stages: Vec::new() }
+  }
+
+  /// Appends a stage and returns the chain, for building chains in one expression.
+  pub fn then<F: TrackingFilter + 'static>(mut self, filter: F) -> FilterChain {
+    self.stages.push(box filter as Box<TrackingFilter + 'static>);
+    self
+  }
+}
+
+impl TrackingFilter for FilterChain {
+
+  fn filter(&mut self, state: &TrackingState) -> TrackingState {
+    let mut state = state.clone();
+    for stage in self.stages.iter_mut() {
+      state = stage.filter(&state);
+    }
+    state
+  }
+
+  fn reset(&mut self) {
+    for stage in self.stages.iter_mut() {
+      stage.reset();
+    }
+  }
+}
+
+/// Iterator adapter applying a filter to a sequence of samples, e.g. a
+/// `recording::RecordingReader`.
+pub struct Filtered<I, F> {
+  samples: I,
+  filter: F,
+}
+
+impl<I: Iterator<TrackingState>, F: TrackingFilter> Filtered<I, F> {
+
+  pub fn new(samples: I, filter: F) -> Filtered<I, F> {
+    Filtered{ samples: samples, filter: filter }
+  }
+
+  pub fn into_inner(self) -> (I, F) {
+    (self.samples, self.filter)
+  }
+}
+
+impl<I: Iterator<TrackingState>, F: TrackingFilter> Iterator<TrackingState> for Filtered<I, F> {
+  fn next(&mut self) -> Option<TrackingState> {
+    self.samples.next().map(|state| self.filter.filter(&state))
+  }
+}
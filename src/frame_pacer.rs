@@ -0,0 +1,171 @@
+//! Render loop pacing driven by `FrameTiming`.
+//!
+//! A frame with `FramePacer` looks like:
+//!
+//! ```ignore
+//! let timing = pacer.begin_frame(&hmd);
+//! // simulation and other work that does not need the head pose
+//! let state = pacer.wait_for_pose(&hmd);
+//! // render both eyes with state.HeadPose
+//! if let Some(dropped) = pacer.end_frame(&hmd) { ... }
+//! ```
+//!
+//! The pacer sleeps until `lead_time` seconds before the frame's
+//! `TimewarpPointSeconds`, so the pose is sampled as late as the render
+//! budget allows. `lead_time` must cover rendering both eyes.
+
+use std::num::Float;
+
+use device::HmdDevice;
+use clock::Clock;
+use ffi::{TrackingState, FrameTiming};
+
+/// A frame that missed its deadline.
+#[deriving(Clone, PartialEq, Show)]
+pub struct DroppedFrame {
+  pub frame_index: i32,
+  /// Seconds by which the frame ended after its timewarp point, or zero if
+  /// the frame was detected by a gap in the frame times.
+  pub late_by: f64,
+  /// Number of vsyncs without a new frame.
+  pub missed_vsyncs: uint,
+}
+
+pub struct FramePacer<C> {
+  clock: C,
+  /// Seconds before `TimewarpPointSeconds` at which `wait_for_pose` returns.
+  pub lead_time: f64,
+  frame_index: i32,
+  timing: Option<FrameTiming>,
+  /// `NextFrameSeconds` of the previous frame.
+  expected_frame: Option<f64>,
+  gap: Option<DroppedFrame>,
+  pose_sample_time: Option<f64>,
+  dropped: uint,
+  frames: uint,
+}
+
+impl<C: Clock> FramePacer<C> {
+
+  pub fn new(clock: C, lead_time: f64) -> FramePacer<C> {
+    FramePacer{
+      clock: clock,
+      lead_time: lead_time,
+      frame_index: 0,
+      timing: None,
+      expected_frame: None,
+      gap: None,
+      pose_sample_time: None,
+      dropped: 0,
+      frames: 0,
+    }
+  }
+
+  pub fn clock(&self) -> &C {
+    &self.clock
+  }
+
+  /// Index of the current or next frame.
+  pub fn frame_index(&self) -> i32 {
+    self.frame_index
+  }
+
+  /// Timing of the current frame, between `begin_frame` and `end_frame`.
+  pub fn timing(&self) -> Option<FrameTiming> {
+    self.timing
+  }
+
+  /// Clock time at which `wait_for_pose` sampled the pose of the current or
+  /// last frame, e.g. for `FrameStats::add_frame`.
+  pub fn pose_sample_time(&self) -> Option<f64> {
+    self.pose_sample_time
+  }
+
+  /// Number of frames ended.
+  pub fn frames(&self) -> uint {
+    self.frames
+  }
+
+  /// Number of dropped frames reported so far.
+  pub fn dropped_frames(&self) -> uint {
+    self.dropped
+  }
+
+  /// Restarts frame timing at `frame_index`, e.g. after a pause.
+  pub fn reset<D: HmdDevice>(&mut self, hmd: &D, frame_index: i32) {
+    hmd.reset_frame_timing(frame_index);
+    self.frame_index = frame_index;
+    self.timing = None;
+    self.expected_frame = None;
+    self.gap = None;
+  }
+
+  /// Starts the frame. If the frame starts one or more vsyncs after the end
+  /// of the previous frame, the skipped vsyncs are reported by `end_frame`.
+  pub fn begin_frame<D: HmdDevice>(&mut self, hmd: &D) -> FrameTiming {
+    let timing = hmd.begin_frame_timing(self.frame_index);
+    self.pose_sample_time = None;
+    let interval = timing.NextFrameSeconds - timing.ThisFrameSeconds;
+    self.gap = match self.expected_frame {
+      Some(expected) if interval > 0.0 && timing.ThisFrameSeconds - expected > interval * 0.5 => {
+        Some(DroppedFrame{
+          frame_index: self.frame_index,
+          late_by: 0.0,
+          missed_vsyncs: ((timing.ThisFrameSeconds - expected) / interval + 0.5) as uint,
+        })
+      }
+      _ => None,
+    };
+    self.timing = Some(timing);
+    timing
+  }
+
+  /// Sleeps until `lead_time` before the timewarp point of the current frame
+  /// and returns the head state predicted for the middle of scanout.
+  pub fn wait_for_pose<D: HmdDevice>(&mut self, hmd: &D) -> TrackingState {
+    let timing = self.current();
+    let now = self.clock.wait_until(timing.TimewarpPointSeconds - self.lead_time);
+    self.pose_sample_time = Some(now);
+    hmd.get_tracking_state(timing.ScanoutMidpointSeconds)
+  }
+
+  /// Ends the frame and returns the dropped frame, if the frame ended after
+  /// its timewarp point or started late.
+  pub fn end_frame<D: HmdDevice>(&mut self, hmd: &D) -> Option<DroppedFrame> {
+    let timing = self.current();
+    let now = self.clock.now();
+    hmd.end_frame_timing();
+
+    let interval = timing.NextFrameSeconds - timing.ThisFrameSeconds;
+    let mut dropped = self.gap.take();
+    if now > timing.TimewarpPointSeconds {
+      let late_by = now - timing.TimewarpPointSeconds;
+      let missed = if interval > 0.0 { 1 + (late_by / interval) as uint } else { 1 };
+      dropped = Some(match dropped {
+        Some(gap) => DroppedFrame{ late_by: late_by, missed_vsyncs: gap.missed_vsyncs + missed, ..gap },
+        None      => DroppedFrame{ frame_index: self.frame_index, late_by: late_by, missed_vsyncs: missed },
+      });
+    }
+
+    self.expected_frame = Some(if now > timing.NextFrameSeconds && interval > 0.0 {
+      // the frame is shown at the first vsync after it ended
+      timing.NextFrameSeconds + ((now - timing.NextFrameSeconds) / interval).ceil() * interval
+    } else {
+      timing.NextFrameSeconds
+    });
+    self.timing = None;
+    self.frame_index += 1;
+    self.frames += 1;
+    if dropped.is_some() {
+      self.dropped += 1;
+    }
+    dropped
+  }
+
+  fn current(&self) -> FrameTiming {
+    match self.timing {
+      Some(timing) => timing,
+      None         => panic!("FramePacer: begin_frame was not called"),
+    }
+  }
+}
@@ -0,0 +1,184 @@
+//! Frame time and latency statistics over a rolling window of frames.
+//!
+//! Feed `FrameStats` the `FrameTiming` of every frame, together with the
+//! time the head pose used for rendering was sampled. Like the SDK's
+//! internal `LatencyStatistics`, it reports the frame rate and latencies
+//! over the recent frames and can print them as a short report.
+
+use std::cmp::{max, min, Equal};
+use std::collections::RingBuf;
+use std::io::IoResult;
+use std::num::Float;
+
+use ffi::FrameTiming;
+
+/// Default number of frames the statistics are computed over.
+pub const DEFAULT_WINDOW: uint = 256;
+
+#[deriving(Clone, Show)]
+struct Sample {
+  frame_time: f64,
+  vsync_interval: f64,
+  missed_vsyncs: uint,
+  photon_latency: Option<f64>,
+}
+
+/// Summary of a series of values, in seconds.
+#[deriving(Clone, Show)]
+pub struct Summary {
+  pub count: uint,
+  pub mean: f64,
+  pub min: f64,
+  pub max: f64,
+  pub p50: f64,
+  pub p95: f64,
+  pub p99: f64,
+}
+
+impl Summary {
+
+  /// Returns None for an empty series.
+  pub fn of(values: &[f64]) -> Option<Summary> {
+    if values.is_empty() {
+      return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Equal));
+    let sum = sorted.iter().fold(0.0, |acc, v| acc + *v);
+    Some(Summary{
+      count: sorted.len(),
+      mean: sum / sorted.len() as f64,
+      min: sorted[0],
+      max: sorted[sorted.len() - 1],
+      p50: percentile(sorted.as_slice(), 50.0),
+      p95: percentile(sorted.as_slice(), 95.0),
+      p99: percentile(sorted.as_slice(), 99.0),
+    })
+  }
+}
+
+/// Nearest rank percentile of sorted, non-empty values.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+  let rank = (p / 100.0 * sorted.len() as f64).ceil() as uint;
+  sorted[min(max(rank, 1), sorted.len()) - 1]
+}
+
+pub struct FrameStats {
+  window: uint,
+  samples: RingBuf<Sample>,
+  frames: uint,
+  missed_vsyncs: uint,
+}
+
+impl FrameStats {
+
+  pub fn new(window: uint) -> FrameStats {
+    FrameStats{ window: max(window, 1), samples: RingBuf::new(), frames: 0, missed_vsyncs: 0 }
+  }
+
+  /// Adds a frame. `pose_sample_time` is when the head pose used to render
+  /// the frame was read, e.g. `clock.now()` right before `get_eye_poses` or
+  /// `FramePacer::pose_sample_time`; pass None if unknown. The photon latency is estimated
+  /// as `ScanoutMidpointSeconds - pose_sample_time`.
+  ///
+  /// A frame misses a vsync for every vsync interval beyond the first that
+  /// passed since the previous frame. The interval is taken from
+  /// `NextFrameSeconds - ThisFrameSeconds`.
+  pub fn add_frame(&mut self, timing: &FrameTiming, pose_sample_time: Option<f64>) {
+    let interval = timing.NextFrameSeconds - timing.ThisFrameSeconds;
+    let frame_time = timing.DeltaSeconds as f64;
+    let missed = if interval > 0.0 && frame_time > 0.0 {
+      max((frame_time / interval).round() as int - 1, 0) as uint
+    } else {
+      0
+    };
+    if self.samples.len() == self.window {
+      self.samples.pop_front();
+    }
+    self.samples.push_back(Sample{
+      frame_time: frame_time,
+      vsync_interval: interval,
+      missed_vsyncs: missed,
+      photon_latency: pose_sample_time.map(|t| timing.ScanoutMidpointSeconds - t),
+    });
+    self.frames += 1;
+    self.missed_vsyncs += missed;
+  }
+
+  pub fn reset(&mut self) {
+    self.samples.clear();
+    self.frames = 0;
+    self.missed_vsyncs = 0;
+  }
+
+  /// Number of frames added since creation or the last reset.
+  pub fn total_frames(&self) -> uint {
+    self.frames
+  }
+
+  /// Missed vsyncs since creation or the last reset.
+  pub fn total_missed_vsyncs(&self) -> uint {
+    self.missed_vsyncs
+  }
+
+  /// Missed vsyncs within the window.
+  pub fn missed_vsyncs(&self) -> uint {
+    self.samples.iter().fold(0, |acc, s| acc + s.missed_vsyncs)
+  }
+
+  /// Frame times within the window. The first frame after
+  /// `reset_frame_timing` reports a delta of zero and is left out.
+  pub fn frame_times(&self) -> Option<Summary> {
+    let values: Vec<f64> = self.samples.iter().map(|s| s.frame_time).filter(|t| *t > 0.0).collect();
+    Summary::of(values.as_slice())
+  }
+
+  /// Frame time percentile within the window, `p` from 0 to 100.
+  pub fn frame_time_percentile(&self, p: f64) -> Option<f64> {
+    let mut values: Vec<f64> = self.samples.iter().map(|s| s.frame_time).filter(|t| *t > 0.0).collect();
+    if values.is_empty() {
+      return None;
+    }
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Equal));
+    Some(percentile(values.as_slice(), p))
+  }
+
+  /// Estimated photon latencies within the window.
+  pub fn photon_latencies(&self) -> Option<Summary> {
+    let values: Vec<f64> = self.samples.iter().filter_map(|s| s.photon_latency).collect();
+    Summary::of(values.as_slice())
+  }
+
+  /// Mean frames per second within the window.
+  pub fn fps(&self) -> Option<f64> {
+    self.frame_times().map(|s| 1.0 / s.mean)
+  }
+
+  /// Refresh rate of the display as seen in the latest frame.
+  pub fn refresh_rate(&self) -> Option<f64> {
+    self.samples.back().and_then(|s| if s.vsync_interval > 0.0 { Some(1.0 / s.vsync_interval) } else { None })
+  }
+
+  /// Writes a human readable report of the window, times in milliseconds.
+  pub fn write_report<W: Writer>(&self, w: &mut W) -> IoResult<()> {
+    try!(writeln!(w, "frames: {} in window, {} total", self.samples.len(), self.frames));
+    match (self.fps(), self.refresh_rate()) {
+      (Some(fps), Some(hz)) => try!(writeln!(w, "fps: {:.1} at {:.1} Hz", fps, hz)),
+      (Some(fps), None)     => try!(writeln!(w, "fps: {:.1}", fps)),
+      _                     => {}
+    }
+    try!(writeln!(w, "missed vsyncs: {} in window, {} total", self.missed_vsyncs(), self.missed_vsyncs));
+    try!(write_summary(w, "frame time", self.frame_times()));
+    try!(write_summary(w, "photon latency", self.photon_latencies()));
+    Ok(())
+  }
+}
+
+fn write_summary<W: Writer>(w: &mut W, name: &str, summary: Option<Summary>) -> IoResult<()> {
+  match summary {
+    Some(s) => writeln!(w, "{}: mean {:.2} min {:.2} max {:.2} p50 {:.2} p95 {:.2} p99 {:.2} ms",
+                        name, s.mean * 1000.0, s.min * 1000.0, s.max * 1000.0,
+                        s.p50 * 1000.0, s.p95 * 1000.0, s.p99 * 1000.0),
+    None    => writeln!(w, "{}: no data", name),
+  }
+}
@@ -0,0 +1,164 @@
+//! Frustum planes and a combined culling frustum for both eyes.
+
+use std::num::Float;
+
+use ffi::{Vector3f, Posef, Matrix4f, FovPort, EyeRenderDesc};
+use api::Ovr;
+
+/// A plane given by `normal . p + d = 0`. Points with a positive
+/// signed distance lie on the inner side of the plane.
+#[deriving(Clone, Show)]
+pub struct Plane {
+  pub normal: Vector3f,
+  pub d: f32,
+}
+
+impl Plane {
+
+  /// Creates a normalized plane from the raw coefficients `a*x + b*y + c*z + d`.
+  pub fn from_coefficients(a: f32, b: f32, c: f32, d: f32) -> Plane {
+    let normal = Vector3f::new(a, b, c);
+    let l = normal.length();
+    if l > 0.0 {
+      Plane{ normal: normal.scale(1.0 / l), d: d / l }
+    } else {
+      Plane{ normal: normal, d: d }
+    }
+  }
+
+  pub fn signed_distance(&self, p: &Vector3f) -> f32 {
+    self.normal.dot(p) + self.d
+  }
+
+  /// Returns the plane moved by `offset`.
+  pub fn translated(&self, offset: &Vector3f) -> Plane {
+    Plane{ normal: self.normal.clone(), d: self.d - self.normal.dot(offset) }
+  }
+
+  /// Returns the plane moved from the local space of `pose` into its parent space.
+  pub fn transformed(&self, pose: &Posef) -> Plane {
+    let normal = pose.Orientation.rotate(&self.normal);
+    let d = self.d - normal.dot(&pose.Position);
+    Plane{ normal: normal, d: d }
+  }
+}
+
+/// Index of each plane in `Frustum::planes`.
+pub const Plane_Left                       : uint = 0;
+pub const Plane_Right                      : uint = 1;
+pub const Plane_Bottom                     : uint = 2;
+pub const Plane_Top                        : uint = 3;
+pub const Plane_Near                       : uint = 4;
+pub const Plane_Far                        : uint = 5;
+
+/// A convex volume bounded by six inward facing planes.
+#[deriving(Clone, Show)]
+pub struct Frustum {
+  pub planes: [Plane, ..6],
+}
+
+impl Frustum {
+
+  /// Extracts the six clip planes of a projection matrix as returned by
+  /// `Ovr::matrix_projection`. The matrix is row major and maps depth to
+  /// `[0, w]`, so the planes are expressed in the space the matrix projects from.
+  pub fn from_projection(m: &Matrix4f) -> Frustum {
+    let r = |i: uint| -> [f32, ..4] { m.M[i] };
+    let (r0, r1, r2, r3) = (r(0), r(1), r(2), r(3));
+    let combine = |a: [f32, ..4], b: [f32, ..4], s: f32| -> Plane {
+      Plane::from_coefficients(a[0] + s * b[0], a[1] + s * b[1], a[2] + s * b[2], a[3] + s * b[3])
+    };
+    Frustum{ planes: [
+      combine(r3, r0,  1.0),
+      combine(r3, r0, -1.0),
+      combine(r3, r1,  1.0),
+      combine(r3, r1, -1.0),
+      combine(r2, r2,  0.0),
+      combine(r3, r2, -1.0),
+    ]}
+  }
+
+  /// Returns the frustum moved from the local space of `pose` into its parent space.
+  pub fn transformed(&self, pose: &Posef) -> Frustum {
+    let t = |p: &Plane| p.transformed(pose);
+    let p = &self.planes;
+    Frustum{ planes: [t(&p[0]), t(&p[1]), t(&p[2]), t(&p[3]), t(&p[4]), t(&p[5])] }
+  }
+
+  /// Returns the frustum translated by `offset`.
+  pub fn translated(&self, offset: &Vector3f) -> Frustum {
+    let t = |p: &Plane| p.translated(offset);
+    let p = &self.planes;
+    Frustum{ planes: [t(&p[0]), t(&p[1]), t(&p[2]), t(&p[3]), t(&p[4]), t(&p[5])] }
+  }
+
+  pub fn contains_point(&self, p: &Vector3f) -> bool {
+    self.planes.iter().all(|plane| plane.signed_distance(p) >= 0.0)
+  }
+
+  /// Returns false only if the sphere is entirely outside of the frustum.
+  pub fn intersects_sphere(&self, center: &Vector3f, radius: f32) -> bool {
+    self.planes.iter().all(|plane| plane.signed_distance(center) >= -radius)
+  }
+
+  /// Returns false only if the axis aligned box is entirely outside of the frustum.
+  /// Like all plane based tests this is conservative near the frustum edges.
+  pub fn intersects_aabb(&self, min: &Vector3f, max: &Vector3f) -> bool {
+    self.planes.iter().all(|plane| {
+      let n = &plane.normal;
+      let farthest = Vector3f::new(if n.x >= 0.0 { max.x } else { min.x },
+                                   if n.y >= 0.0 { max.y } else { min.y },
+                                   if n.z >= 0.0 { max.z } else { min.z });
+      plane.signed_distance(&farthest) >= 0.0
+    })
+  }
+}
+
+/// Computes a single frustum enclosing the view frusta of both eyes, e.g. to
+/// cull the scene once per frame.
+///
+/// The frustum belongs to a virtual camera centered between the eyes. It uses
+/// the widest tangent of both eyes on each side and is moved back along the
+/// view axis until its side planes pass behind both eye positions. The result
+/// is expressed in HMD space, i.e. the space in which the eye positions are
+/// `-HmdToEyeViewOffset`.
+pub fn stereo_frustum(eye_render_desc: &[EyeRenderDesc, ..2], znear: f32, zfar: f32, right_handed: bool) -> Frustum {
+  let (f0, f1) = (&eye_render_desc[0].Fov, &eye_render_desc[1].Fov);
+  let fov = FovPort{
+    UpTan:    f0.UpTan.max(f1.UpTan),
+    DownTan:  f0.DownTan.max(f1.DownTan),
+    LeftTan:  f0.LeftTan.max(f1.LeftTan),
+    RightTan: f0.RightTan.max(f1.RightTan),
+  };
+
+  let eye0 = eye_render_desc[0].HmdToEyeViewOffset.neg();
+  let eye1 = eye_render_desc[1].HmdToEyeViewOffset.neg();
+  let center = eye0.add(&eye1).scale(0.5);
+  let forward = Vector3f::new(0.0, 0.0, if right_handed { -1.0 } else { 1.0 });
+
+  // How far the virtual camera has to move back so that each side plane
+  // passes behind both eyes, and the depth of each eye along the view axis.
+  let mut pull_back = 0f32;
+  let mut eye_depth = [0f32, ..2];
+  for (i, eye) in [eye0, eye1].iter().enumerate() {
+    let rel = eye.sub(&center);
+    let along = rel.dot(&forward);
+    eye_depth[i] = along;
+    let needed = [
+      -rel.x / fov.LeftTan,
+       rel.x / fov.RightTan,
+      -rel.y / fov.DownTan,
+       rel.y / fov.UpTan,
+    ];
+    for n in needed.iter() {
+      pull_back = pull_back.max(*n - along);
+    }
+  }
+
+  let near = pull_back + eye_depth[0].min(eye_depth[1]) + znear;
+  let far = pull_back + eye_depth[0].max(eye_depth[1]) + zfar;
+  let projection = Ovr::matrix_projection(fov, near, far, right_handed);
+
+  let camera = center.sub(&forward.scale(pull_back));
+  Frustum::from_projection(&projection).translated(&camera)
+}
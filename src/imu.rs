@@ -0,0 +1,242 @@
+//! Unit aware access to the raw IMU readings and simple calibration helpers.
+//!
+//! `TrackingState.RawSensorData` holds the readings the SDK used for the
+//! last sensor fusion update, in the sensor frame of the headset. The
+//! estimators below are fed one `SensorData` at a time, e.g. while the
+//! headset lies still on a table or is turned in all directions.
+
+use std::num::Float;
+
+use ffi::{SensorData, Vector3f};
+
+/// Standard gravity in m/s².
+pub const STANDARD_GRAVITY: f32 = 9.80665;
+
+/// Acceleration in m/s².
+#[deriving(Clone, Show)]
+pub struct MetersPerSecondSquared(pub Vector3f);
+
+/// Angular velocity in rad/s.
+#[deriving(Clone, Show)]
+pub struct RadiansPerSecond(pub Vector3f);
+
+/// Magnetic flux density in gauss.
+#[deriving(Clone, Show)]
+pub struct Gauss(pub Vector3f);
+
+/// Temperature in degrees Celsius.
+#[deriving(Clone, PartialEq, PartialOrd, Show)]
+pub struct Celsius(pub f32);
+
+impl SensorData {
+
+  pub fn acceleration(&self) -> MetersPerSecondSquared {
+    MetersPerSecondSquared(self.Accelerometer.clone())
+  }
+
+  pub fn angular_velocity(&self) -> RadiansPerSecond {
+    RadiansPerSecond(self.Gyro.clone())
+  }
+
+  pub fn magnetic_field(&self) -> Gauss {
+    Gauss(self.Magnetometer.clone())
+  }
+
+  pub fn temperature(&self) -> Celsius {
+    Celsius(self.Temperature)
+  }
+
+  /// Time of the reading in seconds, on the `ovr_GetTimeInSeconds` clock.
+  pub fn time(&self) -> f64 {
+    self.TimeInSeconds as f64
+  }
+}
+
+//-----------------------------------------------------------------------------------
+// Gyro bias
+//-----------------------------------------------------------------------------------
+
+/// Estimates the gyro bias as the mean gyro reading over a window in which
+/// the headset does not move.
+///
+/// A sample counts as stationary if the gyro reads less than
+/// `max_angular_speed` and the acceleration is within `max_gravity_error` of
+/// standard gravity. A moving sample discards the current window.
+pub struct GyroBiasEstimator {
+  /// Required length of the stationary window in seconds.
+  pub window: f64,
+  pub max_angular_speed: f32,
+  pub max_gravity_error: f32,
+  sum: Vector3f,
+  count: uint,
+  start: Option<f64>,
+  last: f64,
+}
+
+impl GyroBiasEstimator {
+
+  pub fn new(window: f64) -> GyroBiasEstimator {
+    GyroBiasEstimator{
+      window: window,
+      max_angular_speed: 0.05,
+      max_gravity_error: 0.3,
+      sum: Vector3f::new(0.0, 0.0, 0.0),
+      count: 0,
+      start: None,
+      last: 0.0,
+    }
+  }
+
+  pub fn add(&mut self, data: &SensorData) {
+    let stationary = data.Gyro.length() < self.max_angular_speed
+      && (data.Accelerometer.length() - STANDARD_GRAVITY).abs() < self.max_gravity_error;
+    if !stationary {
+      self.reset();
+      return;
+    }
+    if self.start.is_none() {
+      self.start = Some(data.time());
+    }
+    self.sum = self.sum.add(&data.Gyro);
+    self.count += 1;
+    self.last = data.time();
+  }
+
+  /// Length of the current stationary window in seconds.
+  pub fn stationary_time(&self) -> f64 {
+    match self.start {
+      Some(start) => self.last - start,
+      None        => 0.0,
+    }
+  }
+
+  /// The bias in rad/s, once the headset has been still for `window` seconds.
+  pub fn bias(&self) -> Option<RadiansPerSecond> {
+    if self.count == 0 || self.stationary_time() < self.window {
+      None
+    } else {
+      Some(RadiansPerSecond(self.sum.scale(1.0 / self.count as f32)))
+    }
+  }
+
+  pub fn reset(&mut self) {
+    self.sum = Vector3f::new(0.0, 0.0, 0.0);
+    self.count = 0;
+    self.start = None;
+  }
+}
+
+//-----------------------------------------------------------------------------------
+// Gravity
+//-----------------------------------------------------------------------------------
+
+/// Estimates the gravity vector in the sensor frame by low pass filtering
+/// the accelerometer with the given time constant in seconds. The
+/// accelerometer measures the reaction to gravity, so the estimate points up.
+pub struct GravityEstimator {
+  pub time_constant: f64,
+  estimate: Option<Vector3f>,
+  last: f64,
+}
+
+impl GravityEstimator {
+
+  pub fn new(time_constant: f64) -> GravityEstimator {
+    GravityEstimator{ time_constant: time_constant, estimate: None, last: 0.0 }
+  }
+
+  pub fn add(&mut self, data: &SensorData) {
+    let next = match self.estimate {
+      None => data.Accelerometer.clone(),
+      Some(ref g) => {
+        let dt = (data.time() - self.last).max(0.0);
+        let alpha = if self.time_constant <= 0.0 { 1.0 } else { 1.0 - (-dt / self.time_constant).exp() };
+        g.lerp(&data.Accelerometer, alpha as f32)
+      }
+    };
+    self.estimate = Some(next);
+    self.last = data.time();
+  }
+
+  pub fn gravity(&self) -> Option<MetersPerSecondSquared> {
+    self.estimate.as_ref().map(|g| MetersPerSecondSquared(g.clone()))
+  }
+
+  /// Unit vector pointing up in the sensor frame.
+  pub fn up(&self) -> Option<Vector3f> {
+    self.estimate.as_ref().map(|g| g.normalized())
+  }
+
+  /// Angle in radians between the sensor's +Y axis and up.
+  pub fn tilt(&self) -> Option<f32> {
+    self.up().map(|up| up.y.max(-1.0).min(1.0).acos())
+  }
+
+  pub fn reset(&mut self) {
+    self.estimate = None;
+  }
+}
+
+//-----------------------------------------------------------------------------------
+// Magnetometer hard iron offset
+//-----------------------------------------------------------------------------------
+
+/// Estimates the magnetometer hard iron offset from a sweep in which the
+/// headset is turned in all directions. The offset is the center of the box
+/// bounding all readings, which is adequate once every axis has been pointed
+/// along and against the field.
+pub struct HardIronEstimator {
+  /// Minimum extent of the readings on every axis, in gauss, before an
+  /// offset is reported.
+  pub min_range: f32,
+  min: Vector3f,
+  max: Vector3f,
+  count: uint,
+}
+
+impl HardIronEstimator {
+
+  pub fn new() -> HardIronEstimator {
+    HardIronEstimator{
+      min_range: 0.4,
+      min: Vector3f::new(Float::infinity(), Float::infinity(), Float::infinity()),
+      max: Vector3f::new(Float::neg_infinity(), Float::neg_infinity(), Float::neg_infinity()),
+      count: 0,
+    }
+  }
+
+  pub fn add(&mut self, data: &SensorData) {
+    let m = &data.Magnetometer;
+    self.min = Vector3f::new(self.min.x.min(m.x), self.min.y.min(m.y), self.min.z.min(m.z));
+    self.max = Vector3f::new(self.max.x.max(m.x), self.max.y.max(m.y), self.max.z.max(m.z));
+    self.count += 1;
+  }
+
+  /// Extent of the readings per axis in gauss.
+  pub fn range(&self) -> Vector3f {
+    if self.count == 0 { Vector3f::new(0.0, 0.0, 0.0) } else { self.max.sub(&self.min) }
+  }
+
+  /// The offset to subtract from readings, once the sweep covers at least
+  /// `min_range` on every axis.
+  pub fn offset(&self) -> Option<Gauss> {
+    let r = self.range();
+    if r.x < self.min_range || r.y < self.min_range || r.z < self.min_range {
+      None
+    } else {
+      Some(Gauss(self.min.add(&self.max).scale(0.5)))
+    }
+  }
+
+  /// Strength of the local field in gauss, estimated from the mean half range.
+  pub fn field_strength(&self) -> Option<f32> {
+    self.offset().map(|_| {
+      let r = self.range();
+      (r.x + r.y + r.z) / 6.0
+    })
+  }
+
+  pub fn reset(&mut self) {
+    *self = HardIronEstimator{ min_range: self.min_range, ..HardIronEstimator::new() };
+  }
+}
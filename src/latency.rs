@@ -0,0 +1,122 @@
+//! Frame by frame driver for the Oculus latency tester.
+//!
+//! With the DK1 latency tester the application clears the screen with the
+//! color returned by `ovrHmd_ProcessLatencyTest` while a test runs. With a
+//! DK2 the application draws a small quad with the color returned by
+//! `ovrHmd_GetLatencyTest2DrawColor` instead; SDK distortion rendering does
+//! that automatically. `LatencyTester::update` wraps both and collects the
+//! result once the SDK reports it.
+
+use device::HmdDevice;
+
+/// Latencies of a finished test in milliseconds.
+#[deriving(Clone, PartialEq, Show)]
+pub struct LatencyResult {
+  pub average: f32,
+  pub min: f32,
+  pub max: f32,
+  /// The result string as reported by the SDK.
+  pub text: String,
+}
+
+impl LatencyResult {
+
+  /// Parses a result like
+  /// `RESULT=41.2 (add half Tracker period) [min=39.8 max=43.5]`. The
+  /// average may also be given as `Average=`. Returns None if a number is
+  /// missing.
+  pub fn parse(text: &str) -> Option<LatencyResult> {
+    let average = number_after(text, "RESULT=").or_else(|| number_after(text, "Average="));
+    match (average, number_after(text, "min="), number_after(text, "max=")) {
+      (Some(average), Some(min), Some(max)) => Some(LatencyResult{
+        average: average,
+        min: min,
+        max: max,
+        text: text.to_string(),
+      }),
+      _ => None,
+    }
+  }
+}
+
+/// Parses the decimal number following the first occurrence of `key`.
+fn number_after(text: &str, key: &str) -> Option<f32> {
+  let start = match text.find_str(key) {
+    Some(i) => i + key.len(),
+    None    => return None,
+  };
+  let rest = text.slice_from(start);
+  let end = rest.find(|c: char| !((c >= '0' && c <= '9') || c == '.' || c == '-')).unwrap_or(rest.len());
+  from_str::<f32>(rest.slice_to(end))
+}
+
+#[deriving(Clone, PartialEq, Show)]
+pub enum LatencyTestState {
+  /// No test is running.
+  LatencyIdle,
+  /// A test is running, the screen shows the test colors.
+  LatencyTesting,
+  /// The SDK reported a result.
+  LatencyFinished(LatencyResult),
+  /// The SDK reported a result that could not be parsed.
+  LatencyUnparsed(String),
+}
+
+/// What to draw for the latency tester in the current frame.
+#[deriving(Clone, PartialEq, Show)]
+pub struct LatencyFrame {
+  /// Clear the screen with this color (DK1 latency tester).
+  pub clear_color: Option<[u8, ..3]>,
+  /// Draw the latency test quad with this color (DK2).
+  pub draw_color: Option<[u8, ..3]>,
+}
+
+pub struct LatencyTester {
+  state: LatencyTestState,
+  results: Vec<LatencyResult>,
+}
+
+impl LatencyTester {
+
+  pub fn new() -> LatencyTester {
+    LatencyTester{ state: LatencyIdle, results: Vec::new() }
+  }
+
+  pub fn state(&self) -> &LatencyTestState {
+    &self.state
+  }
+
+  /// All parsed results, oldest first.
+  pub fn results(&self) -> &[LatencyResult] {
+    self.results.as_slice()
+  }
+
+  /// Call once per frame before rendering.
+  pub fn update<D: HmdDevice>(&mut self, hmd: &D) -> LatencyFrame {
+    let frame = LatencyFrame{
+      clear_color: hmd.process_latency_test(),
+      draw_color: hmd.get_latency_test_draw_color(),
+    };
+    if frame.clear_color.is_some() {
+      self.state = LatencyTesting;
+    }
+    // the SDK returns a result only once, and null until then
+    match hmd.get_latency_test_result() {
+      Some(text) => {
+        self.state = match LatencyResult::parse(text.as_slice()) {
+          Some(result) => {
+            self.results.push(result.clone());
+            LatencyFinished(result)
+          }
+          None => LatencyUnparsed(text),
+        };
+      }
+      None => {
+        if self.state == LatencyTesting && frame.clear_color.is_none() {
+          self.state = LatencyIdle;
+        }
+      }
+    }
+    frame
+  }
+}
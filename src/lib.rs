@@ -4,8 +4,21 @@
 #![allow(non_upper_case_globals)]
 #![allow(unused_imports)]
 #![feature(globs)]
+#![feature(macro_rules)]
+#![cfg_attr(feature = "serde", feature(phase))]
 
 extern crate libc;
+extern crate time;
+
+#[cfg(feature = "cgmath")]
+extern crate cgmath;
+#[cfg(feature = "nalgebra")]
+extern crate nalgebra;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+#[phase(plugin)]
+extern crate serde_macros;
 
 use libc::{c_int, c_uint, c_void, c_float, c_double};
 use std::default::Default;
@@ -13,15 +26,68 @@ use std::ptr;
 
 
 
-pub use self::api::HmdType;
-
+pub use self::api::{HmdType, HmdNone, HmdDK1, HmdDKHD, HmdDK2, HmdOther};
+pub use self::api::InitError;
+pub use self::api::{Ovr, Hmd, EyePoses, EyeType, EyeL, EyeR, BitMask, HmdCaps, TrackingCaps, DistortionCaps};
+pub use self::device::HmdDevice;
 
-mod link_settings;
 
 pub mod ffi;
 
+#[cfg(feature = "dynamic")]
+mod dynamic;
+
+#[cfg(feature = "generate-bindings")]
+pub mod raw;
+
 mod api;
 
+mod math;
+
+mod device;
+
+pub mod mock;
+
+pub mod recording;
+
+pub mod replay;
+
+pub mod export;
+
+pub mod prediction;
+
+pub mod filter;
+
+pub mod imu;
+
+pub mod reference_frame;
+
+pub mod frame_stats;
+
+pub mod clock;
+
+pub mod frame_pacer;
+
+pub mod latency;
+
+pub mod timewarp;
+
+pub mod shaders;
+
+pub mod distortion;
+
+pub mod frustum;
+
+pub mod tracking_volume;
+
+pub mod convert;
+
+#[cfg(feature = "cgmath")]
+pub mod cgmath_conv;
+
+#[cfg(feature = "nalgebra")]
+pub mod nalgebra_conv;
+
 
 
 
@@ -0,0 +1,261 @@
+//! Small vector, quaternion and pose helpers on top of the plain ffi math structures.
+
+use std::num::Float;
+
+use ffi::{Vector3f, Quatf, Posef, Matrix4f};
+
+impl Vector3f {
+
+  pub fn new(x: f32, y: f32, z: f32) -> Vector3f {
+    Vector3f{ x: x, y: y, z: z }
+  }
+
+  pub fn add(&self, other: &Vector3f) -> Vector3f {
+    Vector3f::new(self.x + other.x, self.y + other.y, self.z + other.z)
+  }
+
+  pub fn sub(&self, other: &Vector3f) -> Vector3f {
+    Vector3f::new(self.x - other.x, self.y - other.y, self.z - other.z)
+  }
+
+  pub fn scale(&self, s: f32) -> Vector3f {
+    Vector3f::new(self.x * s, self.y * s, self.z * s)
+  }
+
+  pub fn neg(&self) -> Vector3f {
+    Vector3f::new(-self.x, -self.y, -self.z)
+  }
+
+  pub fn dot(&self, other: &Vector3f) -> f32 {
+    self.x * other.x + self.y * other.y + self.z * other.z
+  }
+
+  pub fn cross(&self, other: &Vector3f) -> Vector3f {
+    Vector3f::new(self.y * other.z - self.z * other.y,
+                  self.z * other.x - self.x * other.z,
+                  self.x * other.y - self.y * other.x)
+  }
+
+  pub fn length(&self) -> f32 {
+    self.dot(self).sqrt()
+  }
+
+  /// Returns the unit vector, or the zero vector if the length is zero.
+  pub fn normalized(&self) -> Vector3f {
+    let l = self.length();
+    if l > 0.0 { self.scale(1.0 / l) } else { Vector3f::new(0.0, 0.0, 0.0) }
+  }
+
+  pub fn lerp(&self, other: &Vector3f, t: f32) -> Vector3f {
+    self.add(&other.sub(self).scale(t))
+  }
+}
+
+impl Quatf {
+
+  pub fn new(x: f32, y: f32, z: f32, w: f32) -> Quatf {
+    Quatf{ x: x, y: y, z: z, w: w }
+  }
+
+  pub fn identity() -> Quatf {
+    Quatf::new(0.0, 0.0, 0.0, 1.0)
+  }
+
+  /// Rotation by `angle` radians around the unit vector `axis`.
+  pub fn from_axis_angle(axis: &Vector3f, angle: f32) -> Quatf {
+    let (s, c) = (angle * 0.5).sin_cos();
+    Quatf::new(axis.x * s, axis.y * s, axis.z * s, c)
+  }
+
+  /// Rotation by `|v|` radians around the direction of `v`.
+  pub fn from_rotation_vector(v: &Vector3f) -> Quatf {
+    let angle = v.length();
+    if angle > 0.0 {
+      Quatf::from_axis_angle(&v.scale(1.0 / angle), angle)
+    } else {
+      Quatf::identity()
+    }
+  }
+
+  /// Inverse of `from_rotation_vector`, using the shorter of the two arcs.
+  pub fn to_rotation_vector(&self) -> Vector3f {
+    let q = if self.w < 0.0 { Quatf::new(-self.x, -self.y, -self.z, -self.w) } else { self.clone() };
+    let v = Vector3f::new(q.x, q.y, q.z);
+    let s = v.length();
+    if s > 0.0 {
+      v.scale(2.0 * s.atan2(q.w) / s)
+    } else {
+      Vector3f::new(0.0, 0.0, 0.0)
+    }
+  }
+
+  /// Heading around the vertical (+Y) axis in radians, zero when looking
+  /// down -Z.
+  pub fn yaw(&self) -> f32 {
+    let forward = self.rotate(&Vector3f::new(0.0, 0.0, -1.0));
+    (-forward.x).atan2(-forward.z)
+  }
+
+  /// Yaw, pitch and roll in radians, applied in that order around the Y, X
+  /// and Z axes, like `Quatf::GetEulerAngles<Axis_Y, Axis_X, Axis_Z>` in the SDK.
+  pub fn to_euler_yxz(&self) -> (f32, f32, f32) {
+    let m = self.to_rotation_rows();
+    let pitch = (-m[1][2]).max(-1.0).min(1.0).asin();
+    (m[0][2].atan2(m[2][2]), pitch, m[1][0].atan2(m[1][1]))
+  }
+
+  pub fn dot(&self, other: &Quatf) -> f32 {
+    self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+  }
+
+  pub fn conjugate(&self) -> Quatf {
+    Quatf::new(-self.x, -self.y, -self.z, self.w)
+  }
+
+  /// Returns the unit quaternion, or the identity if the length is zero.
+  pub fn normalized(&self) -> Quatf {
+    let l = self.dot(self).sqrt();
+    if l > 0.0 {
+      Quatf::new(self.x / l, self.y / l, self.z / l, self.w / l)
+    } else {
+      Quatf::identity()
+    }
+  }
+
+  /// Hamilton product, i.e. the rotation `other` followed by `self`.
+  pub fn mul(&self, other: &Quatf) -> Quatf {
+    Quatf::new(self.w * other.x + self.x * other.w + self.y * other.z - self.z * other.y,
+               self.w * other.y - self.x * other.z + self.y * other.w + self.z * other.x,
+               self.w * other.z + self.x * other.y - self.y * other.x + self.z * other.w,
+               self.w * other.w - self.x * other.x - self.y * other.y - self.z * other.z)
+  }
+
+  pub fn rotate(&self, v: &Vector3f) -> Vector3f {
+    let q = Vector3f::new(self.x, self.y, self.z);
+    let t = q.cross(v).scale(2.0);
+    v.add(&t.scale(self.w)).add(&q.cross(&t))
+  }
+
+  /// Spherical linear interpolation along the shorter arc.
+  pub fn slerp(&self, other: &Quatf, t: f32) -> Quatf {
+    let mut cos = self.dot(other);
+    let mut to = other.clone();
+    if cos < 0.0 {
+      cos = -cos;
+      to = Quatf::new(-to.x, -to.y, -to.z, -to.w);
+    }
+    let (a, b) = if cos > 0.9995 {
+      // nearly parallel, fall back to normalized linear interpolation
+      (1.0 - t, t)
+    } else {
+      let angle = cos.acos();
+      let sin = angle.sin();
+      (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+    };
+    Quatf::new(a * self.x + b * to.x, a * self.y + b * to.y,
+               a * self.z + b * to.z, a * self.w + b * to.w).normalized()
+  }
+
+  /// Returns the rotation matrix as rows, for column vectors.
+  pub fn to_rotation_rows(&self) -> [[f32, ..3], ..3] {
+    let (x, y, z, w) = (self.x, self.y, self.z, self.w);
+    [[1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - z * w),       2.0 * (x * z + y * w)],
+     [2.0 * (x * y + z * w),       1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - x * w)],
+     [2.0 * (x * z - y * w),       2.0 * (y * z + x * w),       1.0 - 2.0 * (x * x + y * y)]]
+  }
+
+  /// Inverse of `to_rotation_rows` for an orthonormal matrix.
+  pub fn from_rotation_rows(m: &[[f32, ..3], ..3]) -> Quatf {
+    let trace = m[0][0] + m[1][1] + m[2][2];
+    let q = if trace > 0.0 {
+      let s = (trace + 1.0).sqrt() * 2.0;
+      Quatf::new((m[2][1] - m[1][2]) / s, (m[0][2] - m[2][0]) / s, (m[1][0] - m[0][1]) / s, 0.25 * s)
+    } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+      let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+      Quatf::new(0.25 * s, (m[0][1] + m[1][0]) / s, (m[0][2] + m[2][0]) / s, (m[2][1] - m[1][2]) / s)
+    } else if m[1][1] > m[2][2] {
+      let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+      Quatf::new((m[0][1] + m[1][0]) / s, 0.25 * s, (m[1][2] + m[2][1]) / s, (m[0][2] - m[2][0]) / s)
+    } else {
+      let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+      Quatf::new((m[0][2] + m[2][0]) / s, (m[1][2] + m[2][1]) / s, 0.25 * s, (m[1][0] - m[0][1]) / s)
+    };
+    q.normalized()
+  }
+}
+
+impl Posef {
+
+  pub fn new(orientation: Quatf, position: Vector3f) -> Posef {
+    Posef{ Orientation: orientation, Position: position }
+  }
+
+  pub fn identity() -> Posef {
+    Posef::new(Quatf::identity(), Vector3f::new(0.0, 0.0, 0.0))
+  }
+
+  /// Transforms a point from the local space of the pose into its parent space.
+  pub fn apply(&self, p: &Vector3f) -> Vector3f {
+    self.Orientation.rotate(p).add(&self.Position)
+  }
+
+  pub fn inverse(&self) -> Posef {
+    let inv = self.Orientation.conjugate();
+    let position = inv.rotate(&self.Position).neg();
+    Posef::new(inv, position)
+  }
+
+  /// Returns the pose `other` expressed in the parent space of `self`.
+  pub fn mul(&self, other: &Posef) -> Posef {
+    Posef::new(self.Orientation.mul(&other.Orientation), self.apply(&other.Position))
+  }
+}
+
+impl Matrix4f {
+
+  pub fn identity() -> Matrix4f {
+    Matrix4f{ M: [[1.0, 0.0, 0.0, 0.0],
+                  [0.0, 1.0, 0.0, 0.0],
+                  [0.0, 0.0, 1.0, 0.0],
+                  [0.0, 0.0, 0.0, 1.0]] }
+  }
+
+  /// The rigid transform of `pose`, mapping its local space into its parent space.
+  pub fn from_pose(pose: &Posef) -> Matrix4f {
+    let r = pose.Orientation.to_rotation_rows();
+    let t = &pose.Position;
+    Matrix4f{ M: [[r[0][0], r[0][1], r[0][2], t.x],
+                  [r[1][0], r[1][1], r[1][2], t.y],
+                  [r[2][0], r[2][1], r[2][2], t.z],
+                  [0.0,     0.0,     0.0,     1.0]] }
+  }
+
+  pub fn translation(v: &Vector3f) -> Matrix4f {
+    Matrix4f::from_pose(&Posef::new(Quatf::identity(), v.clone()))
+  }
+
+  /// Matrix product `self * other`, i.e. `other` is applied first.
+  pub fn mul(&self, other: &Matrix4f) -> Matrix4f {
+    let mut m = Matrix4f{ M: [[0.0, ..4], ..4] };
+    for i in range(0u, 4) {
+      for j in range(0u, 4) {
+        m.M[i][j] = range(0u, 4).fold(0.0, |acc, k| acc + self.M[i][k] * other.M[k][j]);
+      }
+    }
+    m
+  }
+
+  /// Inverse of a rotation and translation, like `InvertedHomogeneousTransform`
+  /// in the SDK.
+  pub fn inverted_homogeneous_transform(&self) -> Matrix4f {
+    let m = &self.M;
+    let mut inv = Matrix4f::identity();
+    for i in range(0u, 3) {
+      for j in range(0u, 3) {
+        inv.M[i][j] = m[j][i];
+      }
+      inv.M[i][3] = -(m[0][i] * m[0][3] + m[1][i] * m[1][3] + m[2][i] * m[2][3]);
+    }
+    inv
+  }
+}
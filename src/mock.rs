@@ -0,0 +1,456 @@
+//! A software HMD implementing `HmdDevice` without the native library.
+//!
+//! `MockHmd` describes a DK1, DKHD or DK2 with approximately the resolution,
+//! FOVs, refresh rate and camera frustum the SDK reports for these devices,
+//! and generates tracking data from a `MockMotion`. Frame timing follows an
+//! ideal vsync, starting at time zero. Together with the `dynamic` feature,
+//! code using `HmdDevice` can be tested on machines without libovr.
+
+use std::cell::{Cell, RefCell};
+use std::default::Default;
+use std::num::Float;
+use std::f64::consts::PI;
+use libc::{c_char, c_int, c_uint};
+
+use api::{HmdType, HmdDK1, HmdDKHD, HmdDK2, HmdNone, HmdOther, EyeType, EyeL, EyeR, HmdCaps, TrackingCaps,
+          DistortionCaps, BitMask};
+use device::HmdDevice;
+use replay::Replay;
+use timewarp;
+use ffi;
+use ffi::{HmdDesc, TrackingState, Posef, Quatf, Vector2i, Vector2f, Vector3f, Sizei, Recti,
+          FovPort, EyeRenderDesc, FrameTiming, RenderAPIConfig, Texture, Matrix4f};
+
+/// Default interpupillary distance in meters.
+pub const DEFAULT_IPD: f32 = 0.064;
+
+/// How the mock head moves.
+pub enum MockMotion {
+  /// The head stays at the origin, looking down -Z.
+  Still,
+  /// Procedural motion: the head turns left and right by `yaw_amplitude`
+  /// radians and sways sideways by `sway_amplitude` meters, both with a
+  /// period of `period` seconds.
+  Sway{ yaw_amplitude: f32, sway_amplitude: f32, period: f64 },
+  /// Replays the given states, sorted by `HeadPose.TimeInSeconds`. A query
+  /// returns the latest state at or before the requested time.
+  Scripted(Vec<TrackingState>),
+  /// Plays back a recorded session with interpolation, see `replay::Replay`.
+  Replayed(Replay),
+}
+
+/// Per device constants.
+struct Profile {
+  product_name: &'static [u8],
+  product_id: i16,
+  resolution: Sizei,
+  default_fov: FovPort,
+  max_fov: FovPort,
+  refresh_rate: f64,
+  rolling_right_to_left: bool,
+  has_camera: bool,
+}
+
+fn profile(hmd_type: &HmdType) -> Profile {
+  match *hmd_type {
+    HmdDK2 => Profile{
+      product_name: b"Oculus Rift DK2\0",
+      product_id: 0x0021,
+      resolution: Sizei{ x: 1920, y: 1080 },
+      default_fov: FovPort{ UpTan: 1.3292, DownTan: 1.3292, LeftTan: 1.0586, RightTan: 1.0923 },
+      max_fov: FovPort{ UpTan: 1.4734, DownTan: 1.4734, LeftTan: 1.1737, RightTan: 1.1087 },
+      refresh_rate: 75.0,
+      rolling_right_to_left: true,
+      has_camera: true,
+    },
+    HmdDKHD => Profile{
+      product_name: b"Oculus Rift DK HD\0",
+      product_id: 0x0001,
+      resolution: Sizei{ x: 1920, y: 1080 },
+      default_fov: FovPort{ UpTan: 1.1147, DownTan: 1.1147, LeftTan: 1.0535, RightTan: 1.2348 },
+      max_fov: FovPort{ UpTan: 1.2360, DownTan: 1.2360, LeftTan: 1.1668, RightTan: 1.3694 },
+      refresh_rate: 60.0,
+      rolling_right_to_left: false,
+      has_camera: false,
+    },
+    _ => Profile{
+      product_name: b"Oculus Rift DK1\0",
+      product_id: 0x0001,
+      resolution: Sizei{ x: 1280, y: 800 },
+      default_fov: FovPort{ UpTan: 1.2348, DownTan: 1.2348, LeftTan: 1.0535, RightTan: 1.2348 },
+      max_fov: FovPort{ UpTan: 1.3694, DownTan: 1.3694, LeftTan: 1.1668, RightTan: 1.3694 },
+      refresh_rate: 60.0,
+      rolling_right_to_left: false,
+      has_camera: false,
+    },
+  }
+}
+
+/// The FOV of the right eye is the mirrored FOV of the left eye.
+fn mirror(fov: &FovPort) -> FovPort {
+  FovPort{ UpTan: fov.UpTan, DownTan: fov.DownTan, LeftTan: fov.RightTan, RightTan: fov.LeftTan }
+}
+
+fn eye_index(eye: &EyeType) -> uint {
+  match *eye { EyeL => 0, EyeR => 1 }
+}
+
+pub struct MockHmd {
+  desc: HmdDesc,
+  motion: MockMotion,
+  ipd: f32,
+  frame_interval: f64,
+  enabled_caps: Cell<c_uint>,
+  configured_tracking_caps: Cell<c_uint>,
+  /// Yaw and position removed from all poses by `recenter_pose`.
+  recenter_yaw: Cell<f32>,
+  recenter_position: Cell<(f32, f32, f32)>,
+  /// Frame passed to the last `begin_frame_timing`.
+  frame_index: Cell<i32>,
+  /// Time of the last tracking query, used by `recenter_pose`.
+  last_query_time: Cell<f64>,
+  /// Colors `process_latency_test` still has to return, the next one last.
+  latency_colors: RefCell<Vec<[u8, ..3]>>,
+  latency_running: Cell<bool>,
+  latency_result: RefCell<Option<String>>,
+}
+
+impl MockHmd {
+
+  /// Creates a mock of the given type. `HmdNone` and `HmdOther` fall back to a DK1,
+  /// like the SDK's debug HMD does.
+  pub fn new(hmd_type: HmdType, motion: MockMotion) -> MockHmd {
+    let hmd_type = match hmd_type {
+      HmdNone | HmdOther => HmdDK1,
+      t => t,
+    };
+    let p = profile(&hmd_type);
+
+    let tracking_caps = ffi::TrackingCap_Orientation | ffi::TrackingCap_MagYawCorrection |
+      if p.has_camera { ffi::TrackingCap_Position } else { 0 };
+    let hmd_caps = ffi::HmdCap_Present | ffi::HmdCap_Available |
+      if hmd_type == HmdDK2 { ffi::HmdCap_LowPersistence | ffi::HmdCap_DynamicPrediction } else { 0 };
+    let distortion_caps = ffi::DistortionCap_Chromatic | ffi::DistortionCap_TimeWarp | ffi::DistortionCap_Vignette |
+      ffi::DistortionCap_SRGB | ffi::DistortionCap_FlipInput | ffi::DistortionCap_HqDistortion |
+      if hmd_type == HmdDK2 { ffi::DistortionCap_Overdrive } else { 0 };
+    let eye_render_order = if p.rolling_right_to_left {
+      [ffi::Eye_Right, ffi::Eye_Left]
+    } else {
+      [ffi::Eye_Left, ffi::Eye_Right]
+    };
+    let (h_fov, v_fov, near_z, far_z) = if p.has_camera {
+      (1.2915f32, 0.9425f32, 0.4f32, 2.5f32)
+    } else {
+      (0.0, 0.0, 0.0, 0.0)
+    };
+
+    let desc = HmdDesc{
+      Handle: 0 as *const ffi::Hmd,
+      Type: hmd_type.to_ffi() as c_int,
+      ProductName: p.product_name.as_ptr() as *const c_char,
+      Manufacturer: b"Oculus VR\0".as_ptr() as *const c_char,
+      VendorId: 0x2833,
+      ProductId: p.product_id,
+      SerialNumber: [0, ..24],
+      FirmwareMajor: 0,
+      FirmwareMinor: 0,
+      CameraFrustumHFovInRadians: h_fov,
+      CameraFrustumVFovInRadians: v_fov,
+      CameraFrustumNearZInMeters: near_z,
+      CameraFrustumFarZInMeters: far_z,
+      HmdCaps: hmd_caps,
+      TrackingCaps: tracking_caps,
+      DistortionCaps: distortion_caps,
+      DefaultEyeFov: [p.default_fov.clone(), mirror(&p.default_fov)],
+      MaxEyeFov: [p.max_fov.clone(), mirror(&p.max_fov)],
+      EyeRenderOrder: eye_render_order,
+      Resolution: p.resolution.clone(),
+      WindowsPos: Vector2i{ x: 0, y: 0 },
+      DisplayDeviceName: b"\0".as_ptr() as *const c_char,
+      DisplayId: 0,
+    };
+
+    MockHmd{
+      desc: desc,
+      motion: motion,
+      ipd: DEFAULT_IPD,
+      frame_interval: 1.0 / p.refresh_rate,
+      enabled_caps: Cell::new(hmd_caps),
+      configured_tracking_caps: Cell::new(0),
+      recenter_yaw: Cell::new(0.0),
+      recenter_position: Cell::new((0.0, 0.0, 0.0)),
+      frame_index: Cell::new(0),
+      last_query_time: Cell::new(0.0),
+      latency_colors: RefCell::new(Vec::new()),
+      latency_running: Cell::new(false),
+      latency_result: RefCell::new(None),
+    }
+  }
+
+  pub fn set_ipd(&mut self, ipd: f32) {
+    self.ipd = ipd;
+  }
+
+  /// Gives access to the motion, e.g. to seek a `Replayed` session.
+  pub fn motion_mut(&mut self) -> &mut MockMotion {
+    &mut self.motion
+  }
+
+  pub fn frame_interval(&self) -> f64 {
+    self.frame_interval
+  }
+
+  /// Scripts a run of the DK1 latency tester: `process_latency_test`
+  /// returns `colors` on successive calls, and once they ran out,
+  /// `get_latency_test_result` returns `result` a single time.
+  pub fn script_latency_test(&self, colors: Vec<[u8, ..3]>, result: &str) {
+    let mut colors = colors;
+    colors.reverse();
+    *self.latency_colors.borrow_mut() = colors;
+    *self.latency_result.borrow_mut() = Some(result.to_string());
+  }
+
+  /// The head state generated by the motion, before recentering.
+  fn raw_state(&self, abs_time: f64) -> TrackingState {
+    let mut state: TrackingState = match self.motion {
+      Still => {
+        let mut state: TrackingState = Default::default();
+        state.HeadPose.ThePose = Posef::identity();
+        state
+      }
+      Sway{ yaw_amplitude, sway_amplitude, period } => {
+        let w = 2.0 * PI / period;
+        let (s, c) = (w * abs_time).sin_cos();
+        let (w, s, c) = (w as f32, s as f32, c as f32);
+        let mut state: TrackingState = Default::default();
+        let yaw = yaw_amplitude * s;
+        state.HeadPose.ThePose = Posef::new(Quatf::from_axis_angle(&Vector3f::new(0.0, 1.0, 0.0), yaw),
+                                            Vector3f::new(sway_amplitude * s, 0.0, 0.0));
+        state.HeadPose.AngularVelocity = Vector3f::new(0.0, yaw_amplitude * w * c, 0.0);
+        state.HeadPose.AngularAcceleration = Vector3f::new(0.0, -yaw_amplitude * w * w * s, 0.0);
+        state.HeadPose.LinearVelocity = Vector3f::new(sway_amplitude * w * c, 0.0, 0.0);
+        state.HeadPose.LinearAcceleration = Vector3f::new(-sway_amplitude * w * w * s, 0.0, 0.0);
+        state
+      }
+      Scripted(ref states) => {
+        let mut found = match states.as_slice().first() {
+          Some(first) => first,
+          None        => return Default::default(),
+        };
+        for s in states.iter() {
+          if s.HeadPose.TimeInSeconds > abs_time {
+            break;
+          }
+          found = s;
+        }
+        return found.clone();
+      }
+      Replayed(ref replay) => return replay.get_tracking_state(abs_time),
+    };
+
+    state.HeadPose.TimeInSeconds = abs_time;
+    state.RawSensorData.Accelerometer = Vector3f::new(0.0, 9.81, 0.0);
+    state.RawSensorData.Temperature = 30.0;
+    state.RawSensorData.TimeInSeconds = abs_time as f32;
+    state.StatusFlags = ffi::Status_OrientationTracked | ffi::Status_HmdConnected;
+    if self.desc.TrackingCaps & ffi::TrackingCap_Position != 0 {
+      state.StatusFlags |= ffi::Status_PositionTracked | ffi::Status_PositionConnected | ffi::Status_CameraPoseTracked;
+      // the camera sits one meter in front of the user, looking back at them
+      let camera = Posef::new(Quatf::from_axis_angle(&Vector3f::new(0.0, 1.0, 0.0), PI as f32),
+                              Vector3f::new(0.0, 0.0, -1.0));
+      state.CameraPose = camera.clone();
+      state.LeveledCameraPose = camera;
+    } else {
+      state.CameraPose = Posef::identity();
+      state.LeveledCameraPose = Posef::identity();
+    }
+    state
+  }
+
+  fn recenter_transform(&self) -> Posef {
+    let (x, y, z) = self.recenter_position.get();
+    let origin = Posef::new(Quatf::from_axis_angle(&Vector3f::new(0.0, 1.0, 0.0), self.recenter_yaw.get()),
+                            Vector3f::new(x, y, z));
+    origin.inverse()
+  }
+
+  fn eye_scanout_time(&self, eye: &EyeType) -> f64 {
+    let timing = self.get_frame_timing(self.frame_index.get());
+    timing.EyeScanoutSeconds[eye_index(eye)]
+  }
+}
+
+impl HmdDevice for MockHmd {
+
+  fn get_desc(&self) -> &HmdDesc {
+    &self.desc
+  }
+
+  fn get_last_error(&self) -> Option<String> {
+    None
+  }
+
+  /// Answers the profile keys with the SDK defaults and the configured IPD.
+  fn get_float(&self, property_name: &str, default_val: f32) -> f32 {
+    match property_name {
+      ffi::KEY_IPD           => self.ipd,
+      ffi::KEY_EYE_HEIGHT    => ffi::DEFAULT_EYE_HEIGHT,
+      ffi::KEY_PLAYER_HEIGHT => ffi::DEFAULT_PLAYER_HEIGHT,
+      _                      => default_val,
+    }
+  }
+
+  fn get_enabled_caps(&self) -> HmdCaps {
+    BitMask{ mask: self.enabled_caps.get() }
+  }
+
+  fn set_enabled_caps(&self, caps: HmdCaps) {
+    let writable = caps.mask & ffi::HmdCap_Writable_Mask;
+    self.enabled_caps.set((self.enabled_caps.get() & !ffi::HmdCap_Writable_Mask) | writable);
+  }
+
+  fn configure_tracking(&self, supported_tracking_caps: TrackingCaps, required_tracking_caps: TrackingCaps) -> bool {
+    if required_tracking_caps.mask & !self.desc.TrackingCaps != 0 {
+      return false;
+    }
+    self.configured_tracking_caps.set(supported_tracking_caps.mask & self.desc.TrackingCaps);
+    true
+  }
+
+  fn recenter_pose(&self) {
+    let pose = self.raw_state(self.last_query_time.get()).HeadPose.ThePose;
+    self.recenter_yaw.set(pose.Orientation.yaw());
+    self.recenter_position.set((pose.Position.x, pose.Position.y, pose.Position.z));
+  }
+
+  fn get_tracking_state(&self, abs_time: f64) -> TrackingState {
+    self.last_query_time.set(abs_time);
+    let mut state = self.raw_state(abs_time);
+    let t = self.recenter_transform();
+    state.HeadPose.ThePose = t.mul(&state.HeadPose.ThePose);
+    state.CameraPose = t.mul(&state.CameraPose);
+    state.LeveledCameraPose = t.mul(&state.LeveledCameraPose);
+    let head = &mut state.HeadPose;
+    head.AngularVelocity = t.Orientation.rotate(&head.AngularVelocity);
+    head.LinearVelocity = t.Orientation.rotate(&head.LinearVelocity);
+    head.AngularAcceleration = t.Orientation.rotate(&head.AngularAcceleration);
+    head.LinearAcceleration = t.Orientation.rotate(&head.LinearAcceleration);
+    state
+  }
+
+  /// Uses the pixel density at the center of the lens, like the SDK.
+  fn get_fov_texture_size(&self, eye: EyeType, fov: FovPort, pixels_per_display_pixel: f32) -> Sizei {
+    let ppt = self.get_render_desc(eye, fov.clone()).PixelsPerTanAngleAtCenter;
+    Sizei{
+      x: ((fov.LeftTan + fov.RightTan) * ppt.x * pixels_per_display_pixel).ceil() as c_int,
+      y: ((fov.UpTan + fov.DownTan) * ppt.y * pixels_per_display_pixel).ceil() as c_int,
+    }
+  }
+
+  fn get_render_desc(&self, eye: EyeType, fov: FovPort) -> EyeRenderDesc {
+    let res = &self.desc.Resolution;
+    let half_width = res.x / 2;
+    let i = eye_index(&eye);
+    let default_fov = &self.desc.DefaultEyeFov[i];
+    let offset_x = if i == 0 { self.ipd * 0.5 } else { -self.ipd * 0.5 };
+    EyeRenderDesc{
+      Eye: eye.to_ffi(),
+      Fov: fov,
+      DistortedViewport: Recti{
+        pos: Vector2i{ x: if i == 0 { 0 } else { res.x - half_width }, y: 0 },
+        size: Sizei{ x: half_width, y: res.y },
+      },
+      PixelsPerTanAngleAtCenter: Vector2f{
+        x: half_width as f32 / (default_fov.LeftTan + default_fov.RightTan),
+        y: res.y as f32 / (default_fov.UpTan + default_fov.DownTan),
+      },
+      HmdToEyeViewOffset: Vector3f::new(offset_x, 0.0, 0.0),
+    }
+  }
+
+  /// Nothing is rendered, returns the render descriptions for `eye_fov_in`.
+  fn configure_rendering(&self, _api_config: RenderAPIConfig, _distortion_caps: DistortionCaps,
+                         eye_fov_in: [FovPort, ..2]) -> [EyeRenderDesc, ..2] {
+    [self.get_render_desc(EyeL, eye_fov_in[0].clone()), self.get_render_desc(EyeR, eye_fov_in[1].clone())]
+  }
+
+  fn begin_frame(&self, frame_index: i32) -> FrameTiming {
+    self.begin_frame_timing(frame_index)
+  }
+
+  fn end_frame(&self, _render_pose: [Posef, ..2], _eye_texture: [Texture, ..2]) {
+    self.end_frame_timing();
+  }
+
+  fn get_eye_poses(&self, frame_index: i32, hmd_to_eye_view_offset: [Vector3f, ..2]) -> ([Posef, ..2], TrackingState) {
+    let timing = self.get_frame_timing(frame_index);
+    let state = self.get_tracking_state(timing.ScanoutMidpointSeconds);
+    let head = &state.HeadPose.ThePose;
+    let eye_pose = |offset: &Vector3f| Posef::new(head.Orientation.clone(), head.apply(&offset.neg()));
+    let poses = [eye_pose(&hmd_to_eye_view_offset[0]), eye_pose(&hmd_to_eye_view_offset[1])];
+    (poses, state.clone())
+  }
+
+  fn get_hmd_pose_per_eye(&self, eye: EyeType) -> Posef {
+    self.get_tracking_state(self.eye_scanout_time(&eye)).HeadPose.ThePose
+  }
+
+  /// Timing of an ideal vsync without dropped frames, frame `n` starting at
+  /// `n / refresh_rate`.
+  fn get_frame_timing(&self, frame_index: i32) -> FrameTiming {
+    let interval = self.frame_interval;
+    let this_frame = frame_index as f64 * interval;
+    let next_frame = this_frame + interval;
+    let order = self.desc.EyeRenderOrder;
+    let mut eye_scanout = [0f64, ..2];
+    eye_scanout[order[0] as uint] = next_frame + interval * 0.25;
+    eye_scanout[order[1] as uint] = next_frame + interval * 0.75;
+    FrameTiming{
+      DeltaSeconds: interval as f32,
+      ThisFrameSeconds: this_frame,
+      TimewarpPointSeconds: next_frame - 0.003,
+      NextFrameSeconds: next_frame,
+      ScanoutMidpointSeconds: next_frame + interval * 0.5,
+      EyeScanoutSeconds: eye_scanout,
+    }
+  }
+
+  fn begin_frame_timing(&self, frame_index: i32) -> FrameTiming {
+    self.frame_index.set(frame_index);
+    self.get_frame_timing(frame_index)
+  }
+
+  fn end_frame_timing(&self) {
+    self.frame_index.set(self.frame_index.get() + 1);
+  }
+
+  fn reset_frame_timing(&self, frame_index: i32) {
+    self.frame_index.set(frame_index);
+  }
+
+  /// Predicts the head for the start and the end of the eye's half of the
+  /// current frame's scanout, see `timewarp::predict_eye_timewarp_matrices`.
+  fn get_eye_timewarp_matrices(&self, eye: EyeType, render_pose: Posef) -> [Matrix4f, ..2] {
+    let scanout = self.eye_scanout_time(&eye);
+    let half_eye = self.frame_interval * 0.25;
+    timewarp::predict_eye_timewarp_matrices(self, &render_pose, scanout - half_eye, scanout + half_eye)
+  }
+
+  /// Plays the run set up with `script_latency_test`, if any.
+  fn process_latency_test(&self) -> Option<[u8, ..3]> {
+    let color = self.latency_colors.borrow_mut().pop();
+    self.latency_running.set(color.is_some());
+    color
+  }
+
+  fn get_latency_test_result(&self) -> Option<String> {
+    if self.latency_running.get() {
+      return None;
+    }
+    self.latency_result.borrow_mut().take()
+  }
+
+  fn get_latency_test_draw_color(&self) -> Option<[u8, ..3]> {
+    None
+  }
+}
@@ -0,0 +1,103 @@
+//! Conversions to and from `nalgebra` types.
+//!
+//! `nalgebra` names matrix components `mRC` (row, column), so the row major
+//! `Matrix4f::M` maps to it directly. Poses map to `Iso3`, whose rotation is
+//! stored as a matrix and converted through the quaternion helpers.
+//!
+//! `FovPort` has no counterpart: `PerspMat3` only describes symmetric
+//! frusta, while the FOV of an eye is asymmetric. Convert the matrix from
+//! `Ovr::matrix_projection` instead.
+
+use nalgebra::{Vec2, Vec3, Quat, UnitQuat, Mat3, Mat4, Rot3, Iso3};
+
+use ffi::{Vector2f, Vector3f, Quatf, Matrix4f, Posef};
+use convert::{ToMath, ToOvr};
+
+pub type Isometry = Iso3<f32>;
+
+impl ToMath<Vec2<f32>> for Vector2f {
+  fn to_math(&self) -> Vec2<f32> {
+    Vec2::new(self.x, self.y)
+  }
+}
+
+impl ToOvr<Vector2f> for Vec2<f32> {
+  fn to_ovr(&self) -> Vector2f {
+    Vector2f{ x: self.x, y: self.y }
+  }
+}
+
+impl ToMath<Vec3<f32>> for Vector3f {
+  fn to_math(&self) -> Vec3<f32> {
+    Vec3::new(self.x, self.y, self.z)
+  }
+}
+
+impl ToOvr<Vector3f> for Vec3<f32> {
+  fn to_ovr(&self) -> Vector3f {
+    Vector3f{ x: self.x, y: self.y, z: self.z }
+  }
+}
+
+impl ToMath<Quat<f32>> for Quatf {
+  fn to_math(&self) -> Quat<f32> {
+    Quat::new(self.w, self.x, self.y, self.z)
+  }
+}
+
+impl ToOvr<Quatf> for Quat<f32> {
+  fn to_ovr(&self) -> Quatf {
+    Quatf{ x: self.i, y: self.j, z: self.k, w: self.w }
+  }
+}
+
+impl ToMath<UnitQuat<f32>> for Quatf {
+  fn to_math(&self) -> UnitQuat<f32> {
+    UnitQuat::new_with_quat(self.to_math())
+  }
+}
+
+impl ToOvr<Quatf> for UnitQuat<f32> {
+  fn to_ovr(&self) -> Quatf {
+    self.quat().to_ovr()
+  }
+}
+
+impl ToMath<Mat4<f32>> for Matrix4f {
+  fn to_math(&self) -> Mat4<f32> {
+    let m = &self.M;
+    Mat4::new(m[0][0], m[0][1], m[0][2], m[0][3],
+              m[1][0], m[1][1], m[1][2], m[1][3],
+              m[2][0], m[2][1], m[2][2], m[2][3],
+              m[3][0], m[3][1], m[3][2], m[3][3])
+  }
+}
+
+impl ToOvr<Matrix4f> for Mat4<f32> {
+  fn to_ovr(&self) -> Matrix4f {
+    Matrix4f{ M: [[self.m11, self.m12, self.m13, self.m14],
+                  [self.m21, self.m22, self.m23, self.m24],
+                  [self.m31, self.m32, self.m33, self.m34],
+                  [self.m41, self.m42, self.m43, self.m44]] }
+  }
+}
+
+impl ToMath<Isometry> for Posef {
+  fn to_math(&self) -> Isometry {
+    let r = self.Orientation.to_rotation_rows();
+    let rotation = Mat3::new(r[0][0], r[0][1], r[0][2],
+                             r[1][0], r[1][1], r[1][2],
+                             r[2][0], r[2][1], r[2][2]);
+    Iso3::new_with_rotmat(self.Position.to_math(), unsafe { Rot3::new_with_mat(rotation) })
+  }
+}
+
+impl ToOvr<Posef> for Isometry {
+  fn to_ovr(&self) -> Posef {
+    let m = self.rotation.submat();
+    let rows = [[m.m11, m.m12, m.m13],
+                [m.m21, m.m22, m.m23],
+                [m.m31, m.m32, m.m33]];
+    Posef{ Orientation: Quatf::from_rotation_rows(&rows), Position: self.translation.to_ovr() }
+  }
+}
@@ -0,0 +1,75 @@
+//! Pose prediction from the derivatives in `PoseStatef`.
+//!
+//! The SDK predicts only inside `ovrHmd_GetTrackingState` for an absolute
+//! time. `predict` extrapolates any pose state, e.g. one received over the
+//! network or produced by a replay or a mock HMD.
+
+use std::num::Float;
+
+use clock::Clock;
+use ffi::{PoseStatef, Posef, Quatf};
+
+/// Default upper bound of the prediction interval in seconds.
+pub const DEFAULT_MAX_INTERVAL: f64 = 0.1;
+
+/// Which derivatives are used for the extrapolation.
+#[deriving(Clone, PartialEq, Show)]
+pub enum PredictionMode {
+  /// Uses the velocities only.
+  ConstantVelocity,
+  /// Uses the velocities and accelerations.
+  ConstantAcceleration,
+}
+
+#[deriving(Clone, Show)]
+pub struct PredictionOptions {
+  pub mode: PredictionMode,
+  /// The interval is clamped to `[-max_interval, max_interval]`, since the
+  /// extrapolation quickly becomes worse than no prediction at all.
+  pub max_interval: f64,
+}
+
+impl PredictionOptions {
+
+  pub fn new() -> PredictionOptions {
+    PredictionOptions{ mode: ConstantAcceleration, max_interval: DEFAULT_MAX_INTERVAL }
+  }
+}
+
+/// Predicts `state` `dt` seconds ahead with the default options.
+pub fn predict(state: &PoseStatef, dt: f64) -> PoseStatef {
+  predict_with(state, dt, &PredictionOptions::new())
+}
+
+/// Predicts `state` from its `TimeInSeconds` to the current time of `clock`.
+pub fn predict_to_now<C: Clock>(state: &PoseStatef, clock: &C, options: &PredictionOptions) -> PoseStatef {
+  predict_with(state, clock.now() - state.TimeInSeconds, options)
+}
+
+/// Predicts `state` `dt` seconds ahead. Like the SDK, the angular velocity
+/// and acceleration are taken in the local frame of the pose. The
+/// orientation is rotated by the integrated rotation vector, which is
+/// accurate for the short intervals prediction is used for. Velocities are
+/// advanced by the accelerations in `ConstantAcceleration` mode, and
+/// `TimeInSeconds` by the clamped interval.
+pub fn predict_with(state: &PoseStatef, dt: f64, options: &PredictionOptions) -> PoseStatef {
+  let dt = dt.max(-options.max_interval).min(options.max_interval);
+  let t = dt as f32;
+  let mut result = state.clone();
+
+  let (rotation, translation) = match options.mode {
+    ConstantVelocity => (state.AngularVelocity.scale(t), state.LinearVelocity.scale(t)),
+    ConstantAcceleration => {
+      result.AngularVelocity = state.AngularVelocity.add(&state.AngularAcceleration.scale(t));
+      result.LinearVelocity = state.LinearVelocity.add(&state.LinearAcceleration.scale(t));
+      (state.AngularVelocity.scale(t).add(&state.AngularAcceleration.scale(0.5 * t * t)),
+       state.LinearVelocity.scale(t).add(&state.LinearAcceleration.scale(0.5 * t * t)))
+    }
+  };
+
+  let pose = &state.ThePose;
+  result.ThePose = Posef::new(pose.Orientation.mul(&Quatf::from_rotation_vector(&rotation)).normalized(),
+                              pose.Position.add(&translation));
+  result.TimeInSeconds = state.TimeInSeconds + dt;
+  result
+}
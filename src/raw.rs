@@ -0,0 +1,12 @@
+//! Complete bindings generated by bindgen from the vendored `OVR_CAPI.h` and
+//! `OVR_CAPI_GL.h`. Only built with the `generate-bindings` feature, which
+//! requires `bindgen` and libclang at build time.
+
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+
+include!(concat!(env!("OUT_DIR"), "/raw.rs"))
+
+/// The generated source, used to compare it against the hand written `ffi` module.
+pub static SOURCE: &'static str = include_str!(concat!(env!("OUT_DIR"), "/raw.rs"));
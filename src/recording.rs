@@ -0,0 +1,384 @@
+//! Recording of tracking sessions to a compact binary file.
+//!
+//! A file starts with the magic bytes `OVRREC`, the format version as
+//! little endian `u16` and a length prefixed header. It is followed by one
+//! length prefixed record per sample, each holding a complete `TrackingState`.
+//! All numbers are little endian. Readers skip unknown trailing bytes of the
+//! header and of each record, so later versions can append fields.
+//!
+//! Samples are written as they are taken, so long sessions do not grow memory.
+
+use std::io;
+use std::num::Float;
+use std::io::{IoResult, IoError, File, BufferedWriter, BufferedReader, BufReader, MemWriter};
+use std::string::raw::from_buf;
+use libc::{c_char, c_int, c_uint};
+
+use clock::Clock;
+use device::HmdDevice;
+use ffi::{HmdDesc, TrackingState, PoseStatef, Posef, Quatf, Vector3f, SensorData};
+
+pub const MAGIC: &'static [u8] = b"OVRREC";
+pub const FORMAT_VERSION: u16 = 1;
+
+/// Summary of the recorded HMD and the recording parameters.
+#[deriving(Clone, Show, PartialEq)]
+pub struct RecordingHeader {
+  pub sdk_version: String,
+  pub hmd_type: c_int,
+  pub product_name: String,
+  pub manufacturer: String,
+  pub serial_number: String,
+  pub vendor_id: i16,
+  pub product_id: i16,
+  pub firmware_major: i16,
+  pub firmware_minor: i16,
+  pub resolution: (i32, i32),
+  /// Horizontal and vertical FOV in radians, near and far distance in meters.
+  pub camera_frustum: (f32, f32, f32, f32),
+  pub hmd_caps: c_uint,
+  pub tracking_caps: c_uint,
+  pub distortion_caps: c_uint,
+  /// Samples per second the session was recorded with.
+  pub sample_rate: f64,
+}
+
+unsafe fn string_from_ptr(ptr: *const c_char) -> String {
+  if ptr.is_null() { String::new() } else { from_buf(ptr as *const u8) }
+}
+
+impl RecordingHeader {
+
+  /// Takes the summary from the HMD description. `sdk_version` is usually
+  /// `Ovr::get_version_string`.
+  pub fn from_desc(desc: &HmdDesc, sdk_version: &str, sample_rate: f64) -> RecordingHeader {
+    let serial: Vec<u8> = desc.SerialNumber.iter().take_while(|c| **c != 0).map(|c| *c as u8).collect();
+    RecordingHeader{
+      sdk_version: sdk_version.to_string(),
+      hmd_type: desc.Type,
+      product_name: unsafe { string_from_ptr(desc.ProductName) },
+      manufacturer: unsafe { string_from_ptr(desc.Manufacturer) },
+      serial_number: String::from_utf8_lossy(serial.as_slice()).into_string(),
+      vendor_id: desc.VendorId,
+      product_id: desc.ProductId,
+      firmware_major: desc.FirmwareMajor,
+      firmware_minor: desc.FirmwareMinor,
+      resolution: (desc.Resolution.x, desc.Resolution.y),
+      camera_frustum: (desc.CameraFrustumHFovInRadians, desc.CameraFrustumVFovInRadians,
+                       desc.CameraFrustumNearZInMeters, desc.CameraFrustumFarZInMeters),
+      hmd_caps: desc.HmdCaps,
+      tracking_caps: desc.TrackingCaps,
+      distortion_caps: desc.DistortionCaps,
+      sample_rate: sample_rate,
+    }
+  }
+}
+
+//-----------------------------------------------------------------------------------
+// Encoding
+//-----------------------------------------------------------------------------------
+
+fn invalid(desc: &'static str) -> IoError {
+  IoError{ kind: io::InvalidInput, desc: desc, detail: None }
+}
+
+fn write_string<W: Writer>(w: &mut W, s: &str) -> IoResult<()> {
+  if s.len() > 0xFFFF {
+    return Err(invalid("string too long"));
+  }
+  try!(w.write_le_u16(s.len() as u16));
+  w.write_str(s)
+}
+
+fn read_string<R: Reader>(r: &mut R) -> IoResult<String> {
+  let len = try!(r.read_le_u16()) as uint;
+  let bytes = try!(r.read_exact(len));
+  String::from_utf8(bytes).map_err(|_| invalid("string is not valid UTF-8"))
+}
+
+fn write_vector<W: Writer>(w: &mut W, v: &Vector3f) -> IoResult<()> {
+  try!(w.write_le_f32(v.x));
+  try!(w.write_le_f32(v.y));
+  w.write_le_f32(v.z)
+}
+
+fn read_vector<R: Reader>(r: &mut R) -> IoResult<Vector3f> {
+  Ok(Vector3f{ x: try!(r.read_le_f32()), y: try!(r.read_le_f32()), z: try!(r.read_le_f32()) })
+}
+
+fn write_pose<W: Writer>(w: &mut W, p: &Posef) -> IoResult<()> {
+  let q = &p.Orientation;
+  try!(w.write_le_f32(q.x));
+  try!(w.write_le_f32(q.y));
+  try!(w.write_le_f32(q.z));
+  try!(w.write_le_f32(q.w));
+  write_vector(w, &p.Position)
+}
+
+fn read_pose<R: Reader>(r: &mut R) -> IoResult<Posef> {
+  let q = Quatf{ x: try!(r.read_le_f32()), y: try!(r.read_le_f32()), z: try!(r.read_le_f32()), w: try!(r.read_le_f32()) };
+  Ok(Posef{ Orientation: q, Position: try!(read_vector(r)) })
+}
+
+fn write_header<W: Writer>(w: &mut W, h: &RecordingHeader) -> IoResult<()> {
+  try!(write_string(w, h.sdk_version.as_slice()));
+  try!(w.write_le_i32(h.hmd_type));
+  try!(write_string(w, h.product_name.as_slice()));
+  try!(write_string(w, h.manufacturer.as_slice()));
+  try!(write_string(w, h.serial_number.as_slice()));
+  try!(w.write_le_i16(h.vendor_id));
+  try!(w.write_le_i16(h.product_id));
+  try!(w.write_le_i16(h.firmware_major));
+  try!(w.write_le_i16(h.firmware_minor));
+  let (width, height) = h.resolution;
+  try!(w.write_le_i32(width));
+  try!(w.write_le_i32(height));
+  let (h_fov, v_fov, near_z, far_z) = h.camera_frustum;
+  try!(w.write_le_f32(h_fov));
+  try!(w.write_le_f32(v_fov));
+  try!(w.write_le_f32(near_z));
+  try!(w.write_le_f32(far_z));
+  try!(w.write_le_u32(h.hmd_caps));
+  try!(w.write_le_u32(h.tracking_caps));
+  try!(w.write_le_u32(h.distortion_caps));
+  w.write_le_f64(h.sample_rate)
+}
+
+fn read_header<R: Reader>(r: &mut R) -> IoResult<RecordingHeader> {
+  Ok(RecordingHeader{
+    sdk_version: try!(read_string(r)),
+    hmd_type: try!(r.read_le_i32()),
+    product_name: try!(read_string(r)),
+    manufacturer: try!(read_string(r)),
+    serial_number: try!(read_string(r)),
+    vendor_id: try!(r.read_le_i16()),
+    product_id: try!(r.read_le_i16()),
+    firmware_major: try!(r.read_le_i16()),
+    firmware_minor: try!(r.read_le_i16()),
+    resolution: (try!(r.read_le_i32()), try!(r.read_le_i32())),
+    camera_frustum: (try!(r.read_le_f32()), try!(r.read_le_f32()), try!(r.read_le_f32()), try!(r.read_le_f32())),
+    hmd_caps: try!(r.read_le_u32()),
+    tracking_caps: try!(r.read_le_u32()),
+    distortion_caps: try!(r.read_le_u32()),
+    sample_rate: try!(r.read_le_f64()),
+  })
+}
+
+fn write_sample<W: Writer>(w: &mut W, s: &TrackingState) -> IoResult<()> {
+  let head = &s.HeadPose;
+  try!(write_pose(w, &head.ThePose));
+  try!(write_vector(w, &head.AngularVelocity));
+  try!(write_vector(w, &head.LinearVelocity));
+  try!(write_vector(w, &head.AngularAcceleration));
+  try!(write_vector(w, &head.LinearAcceleration));
+  try!(w.write_le_f64(head.TimeInSeconds));
+  try!(write_pose(w, &s.CameraPose));
+  try!(write_pose(w, &s.LeveledCameraPose));
+  let raw = &s.RawSensorData;
+  try!(write_vector(w, &raw.Accelerometer));
+  try!(write_vector(w, &raw.Gyro));
+  try!(write_vector(w, &raw.Magnetometer));
+  try!(w.write_le_f32(raw.Temperature));
+  try!(w.write_le_f32(raw.TimeInSeconds));
+  try!(w.write_le_u32(s.StatusFlags));
+  try!(w.write_le_f64(s.LastVisionProcessingTime));
+  try!(w.write_le_f64(s.LastVisionFrameLatency));
+  w.write_le_u32(s.LastCameraFrameCounter)
+}
+
+fn read_sample<R: Reader>(r: &mut R) -> IoResult<TrackingState> {
+  let head = PoseStatef{
+    ThePose: try!(read_pose(r)),
+    AngularVelocity: try!(read_vector(r)),
+    LinearVelocity: try!(read_vector(r)),
+    AngularAcceleration: try!(read_vector(r)),
+    LinearAcceleration: try!(read_vector(r)),
+    TimeInSeconds: try!(r.read_le_f64()),
+  };
+  let camera_pose = try!(read_pose(r));
+  let leveled_camera_pose = try!(read_pose(r));
+  let raw = SensorData{
+    Accelerometer: try!(read_vector(r)),
+    Gyro: try!(read_vector(r)),
+    Magnetometer: try!(read_vector(r)),
+    Temperature: try!(r.read_le_f32()),
+    TimeInSeconds: try!(r.read_le_f32()),
+  };
+  Ok(TrackingState{
+    HeadPose: head,
+    CameraPose: camera_pose,
+    LeveledCameraPose: leveled_camera_pose,
+    RawSensorData: raw,
+    StatusFlags: try!(r.read_le_u32()),
+    LastVisionProcessingTime: try!(r.read_le_f64()),
+    LastVisionFrameLatency: try!(r.read_le_f64()),
+    LastCameraFrameCounter: try!(r.read_le_u32()),
+  })
+}
+
+/// Writes `payload` with a `u32` length prefix.
+fn write_block<W: Writer>(w: &mut W, payload: &[u8]) -> IoResult<()> {
+  try!(w.write_le_u32(payload.len() as u32));
+  w.write(payload)
+}
+
+//-----------------------------------------------------------------------------------
+// Writing
+//-----------------------------------------------------------------------------------
+
+/// A destination for tracking samples, e.g. a `Recorder` or one of the
+/// exporters in `export`.
+pub trait SampleSink {
+  fn write_sample(&mut self, state: &TrackingState) -> IoResult<()>;
+  fn flush(&mut self) -> IoResult<()>;
+}
+
+pub struct Recorder<W> {
+  writer: W,
+  samples: uint,
+}
+
+impl Recorder<BufferedWriter<File>> {
+
+  /// Creates (or truncates) the file at `path`.
+  pub fn create(path: &Path, header: &RecordingHeader) -> IoResult<Recorder<BufferedWriter<File>>> {
+    let file = try!(File::create(path));
+    Recorder::new(BufferedWriter::new(file), header)
+  }
+}
+
+impl<W: Writer> Recorder<W> {
+
+  /// Writes the file header and returns a recorder for the samples.
+  pub fn new(mut writer: W, header: &RecordingHeader) -> IoResult<Recorder<W>> {
+    try!(writer.write(MAGIC));
+    try!(writer.write_le_u16(FORMAT_VERSION));
+    let mut buffer = MemWriter::new();
+    try!(write_header(&mut buffer, header));
+    try!(write_block(&mut writer, buffer.get_ref()));
+    Ok(Recorder{ writer: writer, samples: 0 })
+  }
+
+  pub fn write_sample(&mut self, state: &TrackingState) -> IoResult<()> {
+    let mut buffer = MemWriter::new();
+    try!(write_sample(&mut buffer, state));
+    try!(write_block(&mut self.writer, buffer.get_ref()));
+    self.samples += 1;
+    Ok(())
+  }
+
+  /// Number of samples written so far.
+  pub fn samples(&self) -> uint {
+    self.samples
+  }
+
+  pub fn flush(&mut self) -> IoResult<()> {
+    self.writer.flush()
+  }
+
+  pub fn into_inner(self) -> W {
+    self.writer
+  }
+}
+
+impl<W: Writer> SampleSink for Recorder<W> {
+  fn write_sample(&mut self, state: &TrackingState) -> IoResult<()> { self.write_sample(state) }
+  fn flush(&mut self) -> IoResult<()> { self.flush() }
+}
+
+/// Samples `hmd.get_tracking_state` `sample_rate` times per second for
+/// `duration` seconds, waiting on `clock` between samples, and writes every
+/// state to `recorder`. Returns the number of samples written, or an
+/// `InvalidInput` error if `sample_rate` is not a positive finite number.
+pub fn record_session<D: HmdDevice, S: SampleSink, C: Clock>(hmd: &D,
+                                                             recorder: &mut S,
+                                                             clock: &C,
+                                                             sample_rate: f64,
+                                                             duration: f64) -> IoResult<uint> {
+  if !(sample_rate > 0.0) || !sample_rate.is_finite() {
+    return Err(invalid("sample rate must be positive and finite"));
+  }
+  let interval = 1.0 / sample_rate;
+  let start = clock.now();
+  let mut written = 0u;
+  loop {
+    let t = start + written as f64 * interval;
+    if t - start > duration {
+      break;
+    }
+    clock.wait_until(t);
+    try!(recorder.write_sample(&hmd.get_tracking_state(t)));
+    written += 1;
+  }
+  try!(recorder.flush());
+  Ok(written)
+}
+
+//-----------------------------------------------------------------------------------
+// Reading
+//-----------------------------------------------------------------------------------
+
+/// Reads a recording sample by sample. Iterating stops at the end of the
+/// file or at the first error, which is kept in `error`.
+pub struct RecordingReader<R> {
+  reader: R,
+  header: RecordingHeader,
+  pub error: Option<IoError>,
+}
+
+impl RecordingReader<BufferedReader<File>> {
+
+  pub fn open(path: &Path) -> IoResult<RecordingReader<BufferedReader<File>>> {
+    let file = try!(File::open(path));
+    RecordingReader::new(BufferedReader::new(file))
+  }
+}
+
+impl<R: Reader> RecordingReader<R> {
+
+  pub fn new(mut reader: R) -> IoResult<RecordingReader<R>> {
+    let magic = try!(reader.read_exact(MAGIC.len()));
+    if magic.as_slice() != MAGIC {
+      return Err(invalid("not a tracking recording"));
+    }
+    let version = try!(reader.read_le_u16());
+    if version > FORMAT_VERSION {
+      return Err(invalid("unsupported recording format version"));
+    }
+    let block = try!(read_block(&mut reader));
+    let header = try!(read_header(&mut BufReader::new(block.as_slice())));
+    Ok(RecordingReader{ reader: reader, header: header, error: None })
+  }
+
+  pub fn header(&self) -> &RecordingHeader {
+    &self.header
+  }
+
+  /// Returns `Ok(None)` at the end of the file.
+  pub fn read_sample(&mut self) -> IoResult<Option<TrackingState>> {
+    let len = match self.reader.read_le_u32() {
+      Ok(len) => len as uint,
+      Err(ref e) if e.kind == io::EndOfFile => return Ok(None),
+      Err(e) => return Err(e),
+    };
+    let block = try!(self.reader.read_exact(len));
+    read_sample(&mut BufReader::new(block.as_slice())).map(|s| Some(s))
+  }
+}
+
+fn read_block<R: Reader>(r: &mut R) -> IoResult<Vec<u8>> {
+  let len = try!(r.read_le_u32()) as uint;
+  r.read_exact(len)
+}
+
+impl<R: Reader> Iterator<TrackingState> for RecordingReader<R> {
+  fn next(&mut self) -> Option<TrackingState> {
+    if self.error.is_some() {
+      return None;
+    }
+    match self.read_sample() {
+      Ok(sample) => sample,
+      Err(e)     => { self.error = Some(e); None }
+    }
+  }
+}
@@ -0,0 +1,137 @@
+//! Application level reference frames on top of the SDK tracking space.
+//!
+//! `Hmd::recenter_pose` moves the SDK's tracking origin to the current head
+//! position and heading. A `ReferenceFrame` keeps its own origin instead, so
+//! an application can place it freely, recenter only the heading, switch
+//! between seated and standing spaces and keep a log of all recenters.
+
+use device::HmdDevice;
+use ffi;
+use ffi::{TrackingState, Posef, Quatf, Vector3f};
+
+/// The space poses are transformed to.
+#[deriving(Clone, PartialEq, Show)]
+pub enum TrackingSpace {
+  /// The origin is at the eyes of the seated user.
+  SeatedSpace,
+  /// The origin is on the floor below the seated origin, so head positions
+  /// are at about eye height above zero.
+  StandingSpace,
+}
+
+/// A change of the origin.
+#[deriving(Clone, Show)]
+pub struct RecenterEvent {
+  /// Time of the tracking state the recenter was based on, or the time
+  /// passed to `set_origin`.
+  pub time: f64,
+  pub previous_origin: Posef,
+  pub origin: Posef,
+}
+
+#[deriving(Clone, Show)]
+pub struct ReferenceFrame {
+  /// Pose of the seated origin in SDK tracking space.
+  origin: Posef,
+  space: TrackingSpace,
+  /// Height of the eyes above the floor in meters.
+  eye_height: f32,
+  history: Vec<RecenterEvent>,
+}
+
+impl ReferenceFrame {
+
+  /// Starts with the SDK tracking origin as origin.
+  pub fn new(space: TrackingSpace, eye_height: f32) -> ReferenceFrame {
+    ReferenceFrame{ origin: Posef::identity(), space: space, eye_height: eye_height, history: Vec::new() }
+  }
+
+  /// Takes the eye height from the user profile of the device.
+  pub fn from_device<D: HmdDevice>(hmd: &D, space: TrackingSpace) -> ReferenceFrame {
+    ReferenceFrame::new(space, hmd.get_float(ffi::KEY_EYE_HEIGHT, ffi::DEFAULT_EYE_HEIGHT))
+  }
+
+  pub fn origin(&self) -> &Posef {
+    &self.origin
+  }
+
+  pub fn space(&self) -> TrackingSpace {
+    self.space.clone()
+  }
+
+  pub fn set_space(&mut self, space: TrackingSpace) {
+    self.space = space;
+  }
+
+  pub fn eye_height(&self) -> f32 {
+    self.eye_height
+  }
+
+  pub fn set_eye_height(&mut self, eye_height: f32) {
+    self.eye_height = eye_height;
+  }
+
+  /// All origin changes, oldest first.
+  pub fn history(&self) -> &[RecenterEvent] {
+    self.history.as_slice()
+  }
+
+  pub fn clear_history(&mut self) {
+    self.history.clear();
+  }
+
+  /// Sets a user defined origin, given in SDK tracking space.
+  pub fn set_origin(&mut self, origin: Posef, time: f64) {
+    let previous = ::std::mem::replace(&mut self.origin, origin.clone());
+    self.history.push(RecenterEvent{ time: time, previous_origin: previous, origin: origin });
+  }
+
+  /// Moves the origin to the current head position and heading, like
+  /// `Hmd::recenter_pose`. Pitch and roll are ignored.
+  pub fn recenter(&mut self, state: &TrackingState) {
+    let head = &state.HeadPose.ThePose;
+    let yaw = Quatf::from_axis_angle(&Vector3f::new(0.0, 1.0, 0.0), head.Orientation.yaw());
+    self.set_origin(Posef::new(yaw, head.Position.clone()), state.HeadPose.TimeInSeconds);
+  }
+
+  /// Turns the origin to the current heading and moves it horizontally below
+  /// or above the head, keeping the height of the origin.
+  pub fn recenter_yaw(&mut self, state: &TrackingState) {
+    let head = &state.HeadPose.ThePose;
+    let yaw = Quatf::from_axis_angle(&Vector3f::new(0.0, 1.0, 0.0), head.Orientation.yaw());
+    let position = Vector3f::new(head.Position.x, self.origin.Position.y, head.Position.z);
+    self.set_origin(Posef::new(yaw, position), state.HeadPose.TimeInSeconds);
+  }
+
+  /// The transform from SDK tracking space into the chosen space.
+  pub fn transform(&self) -> Posef {
+    let to_seated = self.origin.inverse();
+    match self.space {
+      SeatedSpace   => to_seated,
+      StandingSpace => Posef::new(to_seated.Orientation.clone(),
+                                  to_seated.Position.add(&Vector3f::new(0.0, self.eye_height, 0.0))),
+    }
+  }
+
+  /// Transforms a pose from SDK tracking space into the chosen space.
+  pub fn transform_pose(&self, pose: &Posef) -> Posef {
+    self.transform().mul(pose)
+  }
+
+  /// Transforms the head and camera poses of `state` into the chosen space.
+  /// Velocities and accelerations are rotated along, they are in the same
+  /// frame as the pose, like in the SDK's `Posef * PoseState`.
+  pub fn transform_state(&self, state: &TrackingState) -> TrackingState {
+    let transform = self.transform();
+    let rotation = &transform.Orientation;
+    let mut result = state.clone();
+    result.HeadPose.ThePose = transform.mul(&state.HeadPose.ThePose);
+    result.HeadPose.AngularVelocity = rotation.rotate(&state.HeadPose.AngularVelocity);
+    result.HeadPose.LinearVelocity = rotation.rotate(&state.HeadPose.LinearVelocity);
+    result.HeadPose.AngularAcceleration = rotation.rotate(&state.HeadPose.AngularAcceleration);
+    result.HeadPose.LinearAcceleration = rotation.rotate(&state.HeadPose.LinearAcceleration);
+    result.CameraPose = transform.mul(&state.CameraPose);
+    result.LeveledCameraPose = transform.mul(&state.LeveledCameraPose);
+    result
+  }
+}
@@ -0,0 +1,154 @@
+//! Deterministic playback of recorded tracking sessions.
+//!
+//! `Replay` maps the application's time to a position in the recording and
+//! interpolates between the two neighbouring samples. It can be used on its
+//! own or behind a `mock::MockHmd` via `MockMotion::Replayed`, so render and
+//! game code runs against the recorded data like against a real HMD.
+
+use std::default::Default;
+use std::io::IoResult;
+use std::num::Float;
+
+use api::HmdType;
+use ffi::TrackingState;
+use recording::{RecordingReader, RecordingHeader};
+
+pub struct Replay {
+  header: RecordingHeader,
+  samples: Vec<TrackingState>,
+  looping: bool,
+  time_scale: f64,
+  /// Playback time and recording position (relative to the first sample)
+  /// which correspond to each other.
+  anchor_time: f64,
+  anchor_position: f64,
+}
+
+impl Replay {
+
+  /// Creates a replay of the given samples, which must be sorted by
+  /// `HeadPose.TimeInSeconds`. The recording starts at playback time zero.
+  pub fn new(header: RecordingHeader, samples: Vec<TrackingState>) -> Replay {
+    Replay{
+      header: header,
+      samples: samples,
+      looping: false,
+      time_scale: 1.0,
+      anchor_time: 0.0,
+      anchor_position: 0.0,
+    }
+  }
+
+  /// Loads a whole recording written by `recording::Recorder`.
+  pub fn open(path: &Path) -> IoResult<Replay> {
+    let mut reader = try!(RecordingReader::open(path));
+    let header = reader.header().clone();
+    let samples: Vec<TrackingState> = reader.by_ref().collect();
+    match reader.error {
+      Some(e) => Err(e),
+      None    => Ok(Replay::new(header, samples)),
+    }
+  }
+
+  pub fn header(&self) -> &RecordingHeader {
+    &self.header
+  }
+
+  pub fn hmd_type(&self) -> HmdType {
+    HmdType::from_ffi(self.header.hmd_type as u32)
+  }
+
+  pub fn samples(&self) -> &[TrackingState] {
+    self.samples.as_slice()
+  }
+
+  /// Length of the recording in seconds.
+  pub fn duration(&self) -> f64 {
+    match (self.samples.as_slice().first(), self.samples.as_slice().last()) {
+      (Some(first), Some(last)) => last.HeadPose.TimeInSeconds - first.HeadPose.TimeInSeconds,
+      _ => 0.0,
+    }
+  }
+
+  /// When looping, playback restarts at the beginning after the last sample.
+  /// Otherwise the first and last samples are held.
+  pub fn set_looping(&mut self, looping: bool) {
+    self.looping = looping;
+  }
+
+  /// Changes the playback speed from `abs_time` on, e.g. 0.5 for half speed.
+  pub fn set_time_scale(&mut self, abs_time: f64, time_scale: f64) {
+    self.anchor_position = self.unwrapped_position(abs_time);
+    self.anchor_time = abs_time;
+    self.time_scale = time_scale;
+  }
+
+  /// Makes playback reach `position` seconds into the recording at `abs_time`.
+  pub fn seek(&mut self, abs_time: f64, position: f64) {
+    self.anchor_time = abs_time;
+    self.anchor_position = position;
+  }
+
+  fn unwrapped_position(&self, abs_time: f64) -> f64 {
+    self.anchor_position + (abs_time - self.anchor_time) * self.time_scale
+  }
+
+  /// Position in the recording in seconds relative to the first sample,
+  /// after looping or clamping.
+  pub fn position(&self, abs_time: f64) -> f64 {
+    let position = self.unwrapped_position(abs_time);
+    let duration = self.duration();
+    if duration <= 0.0 {
+      0.0
+    } else if self.looping {
+      let p = position % duration;
+      if p < 0.0 { p + duration } else { p }
+    } else {
+      position.max(0.0).min(duration)
+    }
+  }
+
+  /// Returns the tracking state at `abs_time`. Orientations are interpolated
+  /// by slerp, positions, velocities and accelerations linearly; all other
+  /// fields are taken from the earlier sample. Times are shifted so they read
+  /// as playback time.
+  pub fn get_tracking_state(&self, abs_time: f64) -> TrackingState {
+    let samples = self.samples.as_slice();
+    if samples.is_empty() {
+      return Default::default();
+    }
+    let start = samples[0].HeadPose.TimeInSeconds;
+    let t = start + self.position(abs_time);
+
+    // index of the last sample at or before t
+    let mut lo = 0u;
+    let mut hi = samples.len();
+    while hi - lo > 1 {
+      let mid = (lo + hi) / 2;
+      if samples[mid].HeadPose.TimeInSeconds <= t { lo = mid; } else { hi = mid; }
+    }
+
+    let a = &samples[lo];
+    let mut state = a.clone();
+    if lo + 1 < samples.len() {
+      let b = &samples[lo + 1];
+      let span = b.HeadPose.TimeInSeconds - a.HeadPose.TimeInSeconds;
+      if span > 0.0 {
+        let f = ((t - a.HeadPose.TimeInSeconds) / span) as f32;
+        let (ha, hb) = (&a.HeadPose, &b.HeadPose);
+        let head = &mut state.HeadPose;
+        head.ThePose.Orientation = ha.ThePose.Orientation.slerp(&hb.ThePose.Orientation, f);
+        head.ThePose.Position = ha.ThePose.Position.lerp(&hb.ThePose.Position, f);
+        head.AngularVelocity = ha.AngularVelocity.lerp(&hb.AngularVelocity, f);
+        head.LinearVelocity = ha.LinearVelocity.lerp(&hb.LinearVelocity, f);
+        head.AngularAcceleration = ha.AngularAcceleration.lerp(&hb.AngularAcceleration, f);
+        head.LinearAcceleration = ha.LinearAcceleration.lerp(&hb.LinearAcceleration, f);
+      }
+    }
+
+    let shift = abs_time - t;
+    state.HeadPose.TimeInSeconds = abs_time;
+    state.RawSensorData.TimeInSeconds = (state.RawSensorData.TimeInSeconds as f64 + shift) as f32;
+    state
+  }
+}
@@ -0,0 +1,310 @@
+//! The GLSL programs the SDK uses for distortion rendering with OpenGL.
+//!
+//! The sources are copied from `CAPI/GL/CAPI_GL_DistortionShaders.h`. Like
+//! in the SDK, a shader is compiled as the GLSL prefix for the context's
+//! version followed by the shader source, see `full_source`. The program
+//! depends on the chromatic and timewarp distortion caps. Vignetting needs
+//! no program of its own, the distortion mesh fades it in with the vertex
+//! color.
+
+use ffi;
+use api::DistortionCaps;
+
+/// A uniform of a shader: all are floats, `offset` and `size` are in bytes
+/// within the shader's uniform block, as in the SDK's reflection tables.
+#[deriving(Clone, PartialEq, Show)]
+pub struct Uniform {
+  pub name: &'static str,
+  pub offset: uint,
+  pub size: uint,
+}
+
+#[deriving(Clone, PartialEq, Show)]
+pub struct Shader {
+  pub source: &'static str,
+  pub uniforms: &'static [Uniform],
+}
+
+/// The vertex and fragment shader of a distortion program.
+#[deriving(Clone, PartialEq, Show)]
+pub struct DistortionProgram {
+  pub vertex: Shader,
+  pub fragment: Shader,
+}
+
+/// Returns the GLSL prefix for an OpenGL context version.
+pub fn glsl_prefix(major: uint, minor: uint) -> &'static str {
+  if major < 3 || (major == 3 && minor < 2) { GLSL2_PREFIX } else { GLSL3_PREFIX }
+}
+
+/// The complete source to pass to `glShaderSource`.
+pub fn full_source(prefix: &str, shader: &Shader) -> String {
+  let mut source = String::from_str(prefix);
+  source.push_str(shader.source);
+  source
+}
+
+/// Selects the program for `caps` like the SDK's GL distortion renderer:
+/// `DistortionCap_Chromatic` and `DistortionCap_TimeWarp` pick the vertex
+/// shader, `DistortionCap_Chromatic` the fragment shader.
+pub fn distortion_program(caps: DistortionCaps) -> DistortionProgram {
+  let chromatic = caps.mask & ffi::DistortionCap_Chromatic != 0;
+  let timewarp = caps.mask & ffi::DistortionCap_TimeWarp != 0;
+  let vertex = match (chromatic, timewarp) {
+    (false, false) => Shader{ source: DISTORTION_VS, uniforms: DISTORTION_VS_UNIFORMS },
+    (true, false)  => Shader{ source: DISTORTION_CHROMA_VS, uniforms: DISTORTION_VS_UNIFORMS },
+    (false, true)  => Shader{ source: DISTORTION_TIMEWARP_VS, uniforms: DISTORTION_TIMEWARP_VS_UNIFORMS },
+    (true, true)   => Shader{ source: DISTORTION_TIMEWARP_CHROMA_VS, uniforms: DISTORTION_TIMEWARP_VS_UNIFORMS },
+  };
+  let fragment = if chromatic {
+    Shader{ source: DISTORTION_CHROMA_FS, uniforms: DISTORTION_CHROMA_FS_UNIFORMS }
+  } else {
+    Shader{ source: DISTORTION_FS, uniforms: &[] }
+  };
+  DistortionProgram{ vertex: vertex, fragment: fragment }
+}
+
+/// Uniforms of the vertex shaders without timewarp.
+pub static DISTORTION_VS_UNIFORMS: &'static [Uniform] = &[
+  Uniform{ name: "EyeToSourceUVScale",  offset: 0, size: 8 },
+  Uniform{ name: "EyeToSourceUVOffset", offset: 8, size: 8 },
+];
+
+/// Uniforms of the vertex shaders with timewarp. The SDK's table for
+/// `DistortionTimewarp_vs` lists only the first two, but the shader uses
+/// the rotations as well.
+pub static DISTORTION_TIMEWARP_VS_UNIFORMS: &'static [Uniform] = &[
+  Uniform{ name: "EyeToSourceUVScale",  offset: 0,  size: 8 },
+  Uniform{ name: "EyeToSourceUVOffset", offset: 8,  size: 8 },
+  Uniform{ name: "EyeRotationStart",    offset: 16, size: 64 },
+  Uniform{ name: "EyeRotationEnd",      offset: 80, size: 64 },
+];
+
+/// Uniforms of the chromatic fragment shader.
+pub static DISTORTION_CHROMA_FS_UNIFORMS: &'static [Uniform] = &[
+  Uniform{ name: "OverdriveScales_IsSrgb", offset: 0, size: 12 },
+];
+
+/// All distortion shaders with a name, e.g. for validating them.
+pub static ALL_SHADERS: &'static [(&'static str, &'static str)] = &[
+  ("Distortion_vs", DISTORTION_VS),
+  ("Distortion_fs", DISTORTION_FS),
+  ("DistortionTimewarp_vs", DISTORTION_TIMEWARP_VS),
+  ("DistortionChroma_vs", DISTORTION_CHROMA_VS),
+  ("DistortionChroma_fs", DISTORTION_CHROMA_FS),
+  ("DistortionTimewarpChroma_vs", DISTORTION_TIMEWARP_CHROMA_VS),
+];
+
+//-----------------------------------------------------------------------------------
+// Sources
+//-----------------------------------------------------------------------------------
+
+/// Prepended to every shader for GL versions before 3.2 (`glsl2Prefix`).
+pub const GLSL2_PREFIX: &'static str = concat!(
+  "#version 110\n",
+  "#extension GL_ARB_shader_texture_lod : enable\n",
+  "#extension GL_ARB_draw_buffers : enable\n",
+  "#extension GL_EXT_gpu_shader4 : enable\n",
+  "#define _FRAGCOLOR_DECLARATION\n",
+  "#define _MRTFRAGCOLOR0_DECLARATION\n",
+  "#define _MRTFRAGCOLOR1_DECLARATION\n",
+  "#define _VS_IN attribute\n",
+  "#define _VS_OUT varying\n",
+  "#define _FS_IN varying\n",
+  "#define _TEXTURELOD texture2DLod\n",
+  "#define _TEXTURE texture2D\n",
+  "#define _FRAGCOLOR gl_FragColor\n",
+  "#define _MRTFRAGCOLOR0 gl_FragData[0]\n",
+  "#define _MRTFRAGCOLOR1 gl_FragData[1]\n",
+  "#define _TEXELFETCHDECL vec4 texelFetch(sampler2D tex, ivec2 coord, int lod){ ivec2 size = textureSize2D(tex, lod); return texture2D(tex, vec2(float((coord.x * 2) + 1) / float(size.x * 2), float((coord.y * 2) + 1) / float(size.y * 2))); }\n");
+
+/// Prepended to every shader for GL 3.2 and later (`glsl3Prefix`).
+pub const GLSL3_PREFIX: &'static str = concat!(
+  "#version 150\n",
+  "#define _FRAGCOLOR_DECLARATION out vec4 FragColor;\n",
+  "#define _MRTFRAGCOLOR0_DECLARATION out vec4 FragData0;\n",
+  "#define _MRTFRAGCOLOR1_DECLARATION out vec4 FragData1;\n",
+  "#define _VS_IN in\n",
+  "#define _VS_OUT out\n",
+  "#define _FS_IN in\n",
+  "#define _TEXTURELOD textureLod\n",
+  "#define _TEXTURE texture\n",
+  "#define _FRAGCOLOR FragColor\n",
+  "#define _MRTFRAGCOLOR0 FragData0\n",
+  "#define _MRTFRAGCOLOR1 FragData1\n",
+  "#define _TEXELFETCHDECL\n");
+
+/// Vertex shader without chromatic aberration correction and timewarp (`Distortion_vs`).
+pub const DISTORTION_VS: &'static str = concat!(
+  "uniform vec2 EyeToSourceUVScale;\n",
+  "uniform vec2 EyeToSourceUVOffset;\n",
+  "_VS_IN vec2 Position;\n",
+  "_VS_IN vec4 Color;\n",
+  "_VS_IN vec2 TexCoord0;\n",
+  "_VS_OUT vec4 oColor;\n",
+  "_VS_OUT vec2 oTexCoord0;\n",
+  "void main()\n",
+  "{\n",
+  "   gl_Position.x = Position.x;\n",
+  "   gl_Position.y = Position.y;\n",
+  "   gl_Position.z = 0.5;\n",
+  "   gl_Position.w = 1.0;\n",
+  "   oTexCoord0 = TexCoord0 * EyeToSourceUVScale + EyeToSourceUVOffset;\n",
+  "   oColor = Color;\n",
+  "}\n");
+
+/// Fragment shader without chromatic aberration correction (`Distortion_fs`).
+pub const DISTORTION_FS: &'static str = concat!(
+  "uniform sampler2D Texture0;\n",
+  "_FS_IN vec4 oColor;\n",
+  "_FS_IN vec2 oTexCoord0;\n",
+  "_FRAGCOLOR_DECLARATION\n",
+  "void main()\n",
+  "{\n",
+  "   _FRAGCOLOR = _TEXTURE(Texture0, oTexCoord0, 0.0);\n",
+  "   _FRAGCOLOR.a = 1.0;\n",
+  "}\n");
+
+/// Vertex shader with timewarp (`DistortionTimewarp_vs`).
+pub const DISTORTION_TIMEWARP_VS: &'static str = concat!(
+  "uniform vec2 EyeToSourceUVScale;\n",
+  "uniform vec2 EyeToSourceUVOffset;\n",
+  "uniform mat4 EyeRotationStart;\n",
+  "uniform mat4 EyeRotationEnd;\n",
+  "_VS_IN vec2 Position;\n",
+  "_VS_IN vec4 Color;\n",
+  "_VS_IN vec2 TexCoord0;\n",
+  "_VS_OUT vec4 oColor;\n",
+  "_VS_OUT vec2 oTexCoord0;\n",
+  "void main()\n",
+  "{\n",
+  "   gl_Position.x = Position.x;\n",
+  "   gl_Position.y = Position.y;\n",
+  "   gl_Position.z = 0.0;\n",
+  "   gl_Position.w = 1.0;\n",
+  "   vec3 TanEyeAngle = vec3 ( TexCoord0.x, TexCoord0.y, 1.0 );\n",
+  "   vec3 TransformedStart = (EyeRotationStart * vec4(TanEyeAngle, 0)).xyz;\n",
+  "   vec3 TransformedEnd   = (EyeRotationEnd * vec4(TanEyeAngle, 0)).xyz;\n",
+  "   vec3 Transformed = mix ( TransformedStart, TransformedEnd, Color.a );\n",
+  "   float RecipZ = 1.0 / Transformed.z;\n",
+  "   vec2 Flattened = vec2 ( Transformed.x * RecipZ, Transformed.y * RecipZ );\n",
+  "   vec2 SrcCoord = Flattened * EyeToSourceUVScale + EyeToSourceUVOffset;\n",
+  "   oTexCoord0 = SrcCoord;\n",
+  "   oColor = vec4(Color.r, Color.r, Color.r, Color.r);\n",
+  "}\n");
+
+/// Vertex shader with chromatic aberration correction (`DistortionChroma_vs`).
+pub const DISTORTION_CHROMA_VS: &'static str = concat!(
+  "uniform vec2 EyeToSourceUVScale;\n",
+  "uniform vec2 EyeToSourceUVOffset;\n",
+  "_VS_IN vec2 Position;\n",
+  "_VS_IN vec4 Color;\n",
+  "_VS_IN vec2 TexCoord0;\n",
+  "_VS_IN vec2 TexCoord1;\n",
+  "_VS_IN vec2 TexCoord2;\n",
+  "_VS_OUT vec4 oColor;\n",
+  "_VS_OUT vec2 oTexCoord0;\n",
+  "_VS_OUT vec2 oTexCoord1;\n",
+  "_VS_OUT vec2 oTexCoord2;\n",
+  "void main()\n",
+  "{\n",
+  "   gl_Position.x = Position.x;\n",
+  "   gl_Position.y = Position.y;\n",
+  "   gl_Position.z = 0.5;\n",
+  "   gl_Position.w = 1.0;\n",
+  "   oTexCoord0 = TexCoord0 * EyeToSourceUVScale + EyeToSourceUVOffset;\n",
+  "   oTexCoord1 = TexCoord1 * EyeToSourceUVScale + EyeToSourceUVOffset;\n",
+  "   oTexCoord2 = TexCoord2 * EyeToSourceUVScale + EyeToSourceUVOffset;\n",
+  "   oColor = Color;\n",
+  "}\n");
+
+/// Fragment shader with chromatic aberration correction and overdrive (`DistortionChroma_fs`).
+pub const DISTORTION_CHROMA_FS: &'static str = concat!(
+  "uniform sampler2D Texture0;\n",
+  "uniform sampler2D Texture1;\n",
+  "uniform vec3 OverdriveScales_IsSrgb;\n",
+  "_FS_IN vec4 oColor;\n",
+  "_FS_IN vec2 oTexCoord0;\n",
+  "_FS_IN vec2 oTexCoord1;\n",
+  "_FS_IN vec2 oTexCoord2;\n",
+  "_MRTFRAGCOLOR0_DECLARATION\n",
+  "_MRTFRAGCOLOR1_DECLARATION\n",
+  "_FS_IN vec4 gl_FragCoord;\n",
+  "_TEXELFETCHDECL\n",
+  "void main()\n",
+  "{\n",
+  "   float ResultR = _TEXTURE(Texture0, oTexCoord0, 0.0).r;\n",
+  "   float ResultG = _TEXTURE(Texture0, oTexCoord1, 0.0).g;\n",
+  "   float ResultB = _TEXTURE(Texture0, oTexCoord2, 0.0).b;\n",
+  "   vec3 newColor = vec3(ResultR * oColor.r, ResultG * oColor.g, ResultB * oColor.b);\n",
+  "   _MRTFRAGCOLOR0 = vec4(newColor, 1);\n",
+  "   _MRTFRAGCOLOR1 = _MRTFRAGCOLOR0;\n",
+  "   if(OverdriveScales_IsSrgb.x > 0.0)\n",
+  "   {\n",
+  "       ivec2 pixelCoord = ivec2(gl_FragCoord.x, gl_FragCoord.y);\n",
+  "       vec3 oldColor = texelFetch(Texture1, pixelCoord, 0).rgb;\n",
+  "       vec3 adjustedScales;\n",
+  "       adjustedScales.x = newColor.x > oldColor.x ? OverdriveScales_IsSrgb.x : OverdriveScales_IsSrgb.y;\n",
+  "       adjustedScales.y = newColor.y > oldColor.y ? OverdriveScales_IsSrgb.x : OverdriveScales_IsSrgb.y;\n",
+  "       adjustedScales.z = newColor.z > oldColor.z ? OverdriveScales_IsSrgb.x : OverdriveScales_IsSrgb.y;\n",
+  "		vec3 overdriveColor;\n",
+  "       if(OverdriveScales_IsSrgb.z > 0.0)\n",
+  "		{\n",
+  "           oldColor = pow(oldColor, vec3(1.0/2.2, 1.0/2.2, 1.0/2.2));\n",
+  "			newColor = pow(newColor, vec3(1.0/2.2, 1.0/2.2, 1.0/2.2));\n",
+  "			overdriveColor = clamp(newColor + (newColor - oldColor) * adjustedScales, 0.0, 1.0);\n",
+  "           overdriveColor = pow(overdriveColor, vec3(2.2, 2.2, 2.2));\n",
+  "		}\n",
+  "		else\n",
+  "			overdriveColor = clamp(newColor + (newColor - oldColor) * adjustedScales, 0.0, 1.0);\n",
+  "       _MRTFRAGCOLOR1 = vec4(overdriveColor, 1.0);\n",
+  "   }\n",
+  "}\n");
+
+/// Vertex shader with timewarp and chromatic aberration correction (`DistortionTimewarpChroma_vs`).
+pub const DISTORTION_TIMEWARP_CHROMA_VS: &'static str = concat!(
+  "uniform vec2 EyeToSourceUVScale;\n",
+  "uniform vec2 EyeToSourceUVOffset;\n",
+  "uniform mat4 EyeRotationStart;\n",
+  "uniform mat4 EyeRotationEnd;\n",
+  "_VS_IN vec2 Position;\n",
+  "_VS_IN vec4 Color;\n",
+  "_VS_IN vec2 TexCoord0;\n",
+  "_VS_IN vec2 TexCoord1;\n",
+  "_VS_IN vec2 TexCoord2;\n",
+  "_VS_OUT vec4 oColor;\n",
+  "_VS_OUT vec2 oTexCoord0;\n",
+  "_VS_OUT vec2 oTexCoord1;\n",
+  "_VS_OUT vec2 oTexCoord2;\n",
+  "void main()\n",
+  "{\n",
+  "   gl_Position.x = Position.x;\n",
+  "   gl_Position.y = Position.y;\n",
+  "   gl_Position.z = 0.0;\n",
+  "   gl_Position.w = 1.0;\n",
+  "   vec3 TanEyeAngleR = vec3 ( TexCoord0.x, TexCoord0.y, 1.0 );\n",
+  "   vec3 TanEyeAngleG = vec3 ( TexCoord1.x, TexCoord1.y, 1.0 );\n",
+  "   vec3 TanEyeAngleB = vec3 ( TexCoord2.x, TexCoord2.y, 1.0 );\n",
+  "   vec3 TransformedRStart = (EyeRotationStart * vec4(TanEyeAngleR, 0)).xyz;\n",
+  "   vec3 TransformedGStart = (EyeRotationStart * vec4(TanEyeAngleG, 0)).xyz;\n",
+  "   vec3 TransformedBStart = (EyeRotationStart * vec4(TanEyeAngleB, 0)).xyz;\n",
+  "   vec3 TransformedREnd   = (EyeRotationEnd * vec4(TanEyeAngleR, 0)).xyz;\n",
+  "   vec3 TransformedGEnd   = (EyeRotationEnd * vec4(TanEyeAngleG, 0)).xyz;\n",
+  "   vec3 TransformedBEnd   = (EyeRotationEnd * vec4(TanEyeAngleB, 0)).xyz;\n",
+  "   vec3 TransformedR = mix ( TransformedRStart, TransformedREnd, Color.a );\n",
+  "   vec3 TransformedG = mix ( TransformedGStart, TransformedGEnd, Color.a );\n",
+  "   vec3 TransformedB = mix ( TransformedBStart, TransformedBEnd, Color.a );\n",
+  "   float RecipZR = 1.0 / TransformedR.z;\n",
+  "   float RecipZG = 1.0 / TransformedG.z;\n",
+  "   float RecipZB = 1.0 / TransformedB.z;\n",
+  "   vec2 FlattenedR = vec2 ( TransformedR.x * RecipZR, TransformedR.y * RecipZR );\n",
+  "   vec2 FlattenedG = vec2 ( TransformedG.x * RecipZG, TransformedG.y * RecipZG );\n",
+  "   vec2 FlattenedB = vec2 ( TransformedB.x * RecipZB, TransformedB.y * RecipZB );\n",
+  "   vec2 SrcCoordR = FlattenedR * EyeToSourceUVScale + EyeToSourceUVOffset;\n",
+  "   vec2 SrcCoordG = FlattenedG * EyeToSourceUVScale + EyeToSourceUVOffset;\n",
+  "   vec2 SrcCoordB = FlattenedB * EyeToSourceUVScale + EyeToSourceUVOffset;\n",
+  "   oTexCoord0 = SrcCoordR;\n",
+  "   oTexCoord1 = SrcCoordG;\n",
+  "   oTexCoord2 = SrcCoordB;\n",
+  "   oColor = vec4(Color.r, Color.r, Color.r, Color.r);\n",
+  "}\n");
@@ -0,0 +1,72 @@
+//! Client side computation of timewarp matrices.
+//!
+//! These reproduce what the SDK computes in `ovrHmd_GetEyeTimewarpMatrices`
+//! and `TimewarpComputePoseDelta` (Util_Render_Stereo.h), for client
+//! distortion rendering without the SDK or for checking its output. The
+//! resulting matrices rotate the distortion mesh's view vectors from the
+//! pose an eye was rendered with to the pose at scanout.
+
+use device::HmdDevice;
+use ffi::{Matrix4f, Posef, Quatf, Vector3f};
+
+/// Changes the basis of a delta transform from the tracking convention
+/// (X right, Y up, Z backwards) to the distortion mesh convention (X right,
+/// Y down, Z forwards). This is the similarity transform with
+/// `diag(1, -1, -1)`, where most sign flips cancel.
+fn to_mesh_basis(m: &mut Matrix4f) {
+  m.M[0][1] = -m.M[0][1];
+  m.M[0][2] = -m.M[0][2];
+  m.M[1][0] = -m.M[1][0];
+  m.M[2][0] = -m.M[2][0];
+  m.M[1][3] = -m.M[1][3];
+  m.M[2][3] = -m.M[2][3];
+}
+
+/// Orientation only timewarp matrix from the orientation an eye was rendered
+/// with to the predicted orientation, like the SDK's `GetTimewarpMatrices`.
+pub fn orientation_timewarp_matrix(render_orientation: &Quatf, predicted_orientation: &Quatf) -> Matrix4f {
+  let delta = render_orientation.conjugate().mul(predicted_orientation);
+  let mut m = Matrix4f::from_pose(&Posef::new(delta, Vector3f::new(0.0, 0.0, 0.0)));
+  to_mesh_basis(&mut m);
+  m
+}
+
+/// The start and end matrices for an eye rendered with `render_pose`, given
+/// the head poses predicted for the start and the end of the eye's scanout.
+/// Matches `ovrHmd_GetEyeTimewarpMatrices` for the same predicted poses.
+pub fn eye_timewarp_matrices(render_pose: &Posef, start_pose: &Posef, end_pose: &Posef) -> [Matrix4f, ..2] {
+  [orientation_timewarp_matrix(&render_pose.Orientation, &start_pose.Orientation),
+   orientation_timewarp_matrix(&render_pose.Orientation, &end_pose.Orientation)]
+}
+
+/// Like `eye_timewarp_matrices`, but predicts the head poses with
+/// `hmd.get_tracking_state` at the given scanout start and end times.
+pub fn predict_eye_timewarp_matrices<D: HmdDevice>(hmd: &D, render_pose: &Posef,
+                                                   scanout_start: f64, scanout_end: f64) -> [Matrix4f, ..2] {
+  let start = hmd.get_tracking_state(scanout_start);
+  let end = hmd.get_tracking_state(scanout_end);
+  eye_timewarp_matrices(render_pose, &start.HeadPose.ThePose, &end.HeadPose.ThePose)
+}
+
+/// Port of `TimewarpComputePoseDelta`: the transform from the predicted
+/// view to the rendered view, in the distortion mesh basis. The view
+/// matrices map world space into view space, see `view_from_world`, and
+/// `hmd_to_eye_view_offset` is applied after both.
+///
+/// The SDK's `TimewarpMachine` calls its version with `Matrix4f(pose)`,
+/// which maps view space into world space instead. Pass
+/// `view_from_world(pose)` here; for pure rotations the result then equals
+/// `orientation_timewarp_matrix`.
+pub fn timewarp_compute_pose_delta(rendered_view_from_world: &Matrix4f,
+                                   predicted_view_from_world: &Matrix4f,
+                                   hmd_to_eye_view_offset: &Matrix4f) -> Matrix4f {
+  let world_from_predicted_view = hmd_to_eye_view_offset.mul(predicted_view_from_world).inverted_homogeneous_transform();
+  let mut m = hmd_to_eye_view_offset.mul(rendered_view_from_world).mul(&world_from_predicted_view);
+  to_mesh_basis(&mut m);
+  m
+}
+
+/// The view matrix of a camera at `pose`.
+pub fn view_from_world(pose: &Posef) -> Matrix4f {
+  Matrix4f::from_pose(pose).inverted_homogeneous_transform()
+}
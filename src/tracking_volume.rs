@@ -0,0 +1,106 @@
+//! The volume in which the positional tracking camera sees the headset.
+
+use std::num::Float;
+
+use ffi;
+use ffi::{HmdDesc, TrackingState, Posef, Vector3f};
+use frustum::{Frustum, Plane};
+
+/// The camera frustum in tracking space.
+///
+/// Like the eye cameras, the tracking camera looks down the -Z axis of its
+/// pose. Head positions from `TrackingState.HeadPose` are in the same space
+/// as `TrackingState.CameraPose`, so that is the pose to build the volume
+/// from for inside tests. `LeveledCameraPose` removes the camera tilt and is
+/// meant for drawing the volume in a leveled world.
+#[deriving(Clone, Show)]
+pub struct TrackingVolume {
+  pub camera_pose: Posef,
+  /// Tangents of the half field of view angles.
+  pub h_tan: f32,
+  pub v_tan: f32,
+  pub near_z: f32,
+  pub far_z: f32,
+  /// The planes in tracking space, indexed by `frustum::Plane_*`.
+  pub frustum: Frustum,
+}
+
+impl TrackingVolume {
+
+  /// Returns None if the HMD has no positional tracking camera.
+  pub fn new(desc: &HmdDesc, camera_pose: &Posef) -> Option<TrackingVolume> {
+    if desc.TrackingCaps & ffi::TrackingCap_Position == 0 || desc.CameraFrustumHFovInRadians <= 0.0 {
+      return None;
+    }
+    let h_tan = (desc.CameraFrustumHFovInRadians * 0.5).tan();
+    let v_tan = (desc.CameraFrustumVFovInRadians * 0.5).tan();
+    let (near_z, far_z) = (desc.CameraFrustumNearZInMeters, desc.CameraFrustumFarZInMeters);
+    let local = Frustum{ planes: [
+      Plane::from_coefficients( 1.0,  0.0, -h_tan, 0.0),
+      Plane::from_coefficients(-1.0,  0.0, -h_tan, 0.0),
+      Plane::from_coefficients( 0.0,  1.0, -v_tan, 0.0),
+      Plane::from_coefficients( 0.0, -1.0, -v_tan, 0.0),
+      Plane::from_coefficients( 0.0,  0.0, -1.0, -near_z),
+      Plane::from_coefficients( 0.0,  0.0,  1.0,  far_z),
+    ]};
+    Some(TrackingVolume{
+      camera_pose: camera_pose.clone(),
+      h_tan: h_tan,
+      v_tan: v_tan,
+      near_z: near_z,
+      far_z: far_z,
+      frustum: local.transformed(camera_pose),
+    })
+  }
+
+  /// Builds the volume from the `CameraPose` of a tracking state.
+  pub fn from_state(desc: &HmdDesc, state: &TrackingState) -> Option<TrackingVolume> {
+    TrackingVolume::new(desc, &state.CameraPose)
+  }
+
+  pub fn contains(&self, position: &Vector3f) -> bool {
+    self.frustum.contains_point(position)
+  }
+
+  /// Signed distance in meters to the nearest boundary plane, positive
+  /// inside the volume. Inside this is the exact distance to the boundary;
+  /// outside it is a lower bound of the distance to the volume.
+  pub fn distance_to_boundary(&self, position: &Vector3f) -> f32 {
+    let (_, distance) = self.closest_plane(position);
+    distance
+  }
+
+  /// Index (`frustum::Plane_*`) of the boundary plane nearest to the
+  /// position, and the signed distance to it. Outside the volume this is the
+  /// plane that is crossed the most.
+  pub fn closest_plane(&self, position: &Vector3f) -> (uint, f32) {
+    let (mut index, mut best) = (0u, Float::infinity());
+    for (i, plane) in self.frustum.planes.iter().enumerate() {
+      let distance = plane.signed_distance(position);
+      if distance < best {
+        index = i;
+        best = distance;
+      }
+    }
+    (index, best)
+  }
+
+  /// The eight corners in tracking space: the near rectangle followed by
+  /// the far rectangle, each as left bottom, right bottom, right top, left top.
+  pub fn corners(&self) -> [Vector3f, ..8] {
+    let corner = |x: f32, y: f32, depth: f32| -> Vector3f {
+      self.camera_pose.apply(&Vector3f::new(x * self.h_tan * depth, y * self.v_tan * depth, -depth))
+    };
+    let (n, f) = (self.near_z, self.far_z);
+    [corner(-1.0, -1.0, n), corner(1.0, -1.0, n), corner(1.0, 1.0, n), corner(-1.0, 1.0, n),
+     corner(-1.0, -1.0, f), corner(1.0, -1.0, f), corner(1.0, 1.0, f), corner(-1.0, 1.0, f)]
+  }
+
+  /// Line segments between the corners for debug drawing, as index pairs
+  /// into `corners()`.
+  pub fn edges() -> [(uint, uint), ..12] {
+    [(0, 1), (1, 2), (2, 3), (3, 0),
+     (4, 5), (5, 6), (6, 7), (7, 4),
+     (0, 4), (1, 5), (2, 6), (3, 7)]
+  }
+}
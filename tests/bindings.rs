@@ -0,0 +1,274 @@
+//! Compares the hand written `ffi` module against the bindings generated by
+//! bindgen (`--features generate-bindings`). Reports every function or struct
+//! of the SDK headers that is missing in `ffi`, or whose argument, return
+//! or field types differ. Types are compared after resolving the `pub type`
+//! aliases of both sources, so e.g. `ovrBool` and `OvrBool` both become
+//! `c_char` and `ovrHmdType` becomes the `c_uint` that `ffi` uses for enums.
+
+#![allow(dead_code)]
+
+extern crate "libovr-rs" as ovr;
+
+use std::collections::HashMap;
+
+static FFI_SOURCE: &'static str = include_str!("../src/ffi.rs");
+
+/// Functions which are deliberately not declared in `ffi`.
+static IGNORED_FUNCTIONS: &'static [&'static str] = &[];
+
+/// Normalized `ffi` types which stand for a different generated type.
+static EQUIVALENT_TYPES: &'static [(&'static str, &'static str)] = &[
+  // the ovrHmd handle, declared as a pointer to an opaque type in ffi
+  ("*mutHmd", "*constHmdDesc"),
+  ("*constHmd", "*constHmdStruct"),
+];
+
+/// Arguments declared `*const` in `ffi` although the header lacks the
+/// `const`, given as function name and argument index.
+static CONST_ARGUMENTS: &'static [(&'static str, uint)] = &[
+  ("ovrHmd_GetEyePoses", 2),
+];
+
+/// Maps the generated names like `ovrHmdDesc`, `Struct_ovrHmdDesc_` to the
+/// names used in `ffi`.
+fn normalize_name(name: &str) -> String {
+  let name = name.trim();
+  let name = if name.starts_with("Struct_") { name.slice_from(7) } else { name };
+  let name = if name.starts_with("ovr") { name.slice_from(3) } else { name };
+  name.trim_right_chars('_').to_string()
+}
+
+/// Removes line comments and the commented out blocks of `ffi.rs`.
+fn strip_comments(source: &str) -> String {
+  let mut out = String::new();
+  let mut in_block = false;
+  for line in source.lines() {
+    let mut line = line;
+    if in_block {
+      match line.find_str("*/") {
+        Some(i) => { line = line.slice_from(i + 2); in_block = false; }
+        None    => continue,
+      }
+    }
+    if let Some(i) = line.find_str("/*") {
+      line = line.slice_to(i);
+      in_block = true;
+    }
+    if let Some(i) = line.find_str("//") {
+      line = line.slice_to(i);
+    }
+    out.push_str(line);
+    out.push('\n');
+  }
+  out
+}
+
+/// Splits the comma separated items between the parentheses or braces
+/// that start at the beginning of `s`. Returns them with the rest of `s`
+/// after the closing parenthesis or brace.
+fn split_items(s: &str) -> Option<(Vec<String>, &str)> {
+  let mut depth = 0i;
+  let mut items = Vec::new();
+  let mut item = String::new();
+  for (i, c) in s.char_indices() {
+    match c {
+      '(' | '{' | '[' => { if depth > 0 { item.push(c); } depth += 1; }
+      ')' | '}' | ']' => {
+        depth -= 1;
+        if depth == 0 {
+          if !item.as_slice().trim().is_empty() {
+            items.push(item.as_slice().trim().to_string());
+          }
+          return Some((items, s.slice_from(i + 1)));
+        }
+        item.push(c);
+      }
+      ',' if depth == 1 => {
+        if !item.as_slice().trim().is_empty() {
+          items.push(item.as_slice().trim().to_string());
+        }
+        item = String::new();
+      }
+      _ if depth >= 1 => item.push(c),
+      _ => {}
+    }
+  }
+  None
+}
+
+/// The type of a `name: Type` argument or field.
+fn item_type(item: &str) -> String {
+  let item = item.trim();
+  let item = if item.starts_with("pub ") { item.slice_from(4) } else { item };
+  match item.find(':') {
+    Some(i) => item.slice_from(i + 1).trim().to_string(),
+    None    => item.to_string(),
+  }
+}
+
+/// Collects the `pub type Name = Type;` aliases.
+fn aliases(source: &str) -> HashMap<String, String> {
+  let source = strip_comments(source);
+  let mut result = HashMap::new();
+  for part in source.split_str("pub type ").skip(1) {
+    let (eq, end) = match (part.find('='), part.find(';')) {
+      (Some(eq), Some(end)) if eq < end => (eq, end),
+      _ => continue,
+    };
+    result.insert(part.slice_to(eq).trim().to_string(), part.slice(eq + 1, end).trim().to_string());
+  }
+  result
+}
+
+/// Brings a type into a comparable form: without whitespace, `libc::`
+/// paths and integer suffixes of array lengths, with aliases resolved and
+/// names normalized like `normalize_name`.
+fn normalize_type(ty: &str, aliases: &HashMap<String, String>, depth: uint) -> String {
+  let ty = ty.replace("::libc::", "").replace("libc::", "");
+  let mut out = String::new();
+  let mut word = String::new();
+  let flush = |word: &mut String, out: &mut String| {
+    if word.is_empty() {
+      return;
+    }
+    let w = word.as_slice();
+    if w.char_at(0) >= '0' && w.char_at(0) <= '9' {
+      // array lengths like `2u` or `24usize`
+      out.push_str(w.trim_right_chars(|c: char| c < '0' || c > '9'));
+    } else if w == "const" || w == "mut" {
+      out.push_str(w);
+    } else {
+      match aliases.get(&w.to_string()) {
+        Some(target) if depth < 8 => out.push_str(normalize_type(target.as_slice(), aliases, depth + 1).as_slice()),
+        _ => out.push_str(normalize_name(w).as_slice()),
+      }
+    }
+    word.clear();
+  };
+  for c in ty.as_slice().chars() {
+    if c.is_alphanumeric() || c == '_' {
+      word.push(c);
+    } else {
+      flush(&mut word, &mut out);
+      if !c.is_whitespace() {
+        out.push(c);
+      }
+    }
+  }
+  flush(&mut word, &mut out);
+  out
+}
+
+/// Maps the `ffi` spelling of a normalized type to the generated one.
+fn equivalent(ty: String) -> String {
+  match EQUIVALENT_TYPES.iter().find(|&&(ffi, _)| ffi == ty.as_slice()) {
+    Some(&(_, generated)) => generated.to_string(),
+    None => ty,
+  }
+}
+
+/// A function's normalized argument types and return type.
+#[deriving(PartialEq, Show)]
+struct Signature {
+  args: Vec<String>,
+  ret: String,
+}
+
+/// Collects `pub fn` declarations with their signature.
+fn functions(source: &str) -> HashMap<String, Signature> {
+  let aliases = aliases(source);
+  let source = strip_comments(source);
+  let mut result = HashMap::new();
+  for part in source.split_str("pub fn ").skip(1) {
+    let paren = match part.find('(') { Some(i) => i, None => continue };
+    let name = part.slice_to(paren).trim().to_string();
+    if let Some((items, rest)) = split_items(part.slice_from(paren)) {
+      let end = rest.find(|c: char| c == ';' || c == '{').unwrap_or(rest.len());
+      let rest = rest.slice_to(end).trim();
+      let ret = if rest.starts_with("->") { rest.slice_from(2) } else { "()" };
+      result.insert(name, Signature{
+        args: items.iter().map(|i| equivalent(normalize_type(item_type(i.as_slice()).as_slice(), &aliases, 0))).collect(),
+        ret: equivalent(normalize_type(ret, &aliases, 0)),
+      });
+    }
+  }
+  result
+}
+
+/// Collects `pub struct` declarations with their normalized field types.
+fn structs(source: &str) -> HashMap<String, Vec<String>> {
+  let aliases = aliases(source);
+  let source = strip_comments(source);
+  let mut result = HashMap::new();
+  for part in source.split_str("pub struct ").skip(1) {
+    let brace = match part.find('{') { Some(i) => i, None => continue };
+    let name = normalize_name(part.slice_to(brace));
+    if let Some((items, _)) = split_items(part.slice_from(brace)) {
+      result.insert(name, items.iter().map(|i| equivalent(normalize_type(item_type(i.as_slice()).as_slice(), &aliases, 0))).collect());
+    }
+  }
+  result
+}
+
+/// Describes how `ffi` differs from `generated`, empty if they agree.
+fn compare_signatures(name: &str, ffi: &Signature, generated: &Signature) -> Vec<String> {
+  let mut problems = Vec::new();
+  if ffi.args.len() != generated.args.len() {
+    problems.push(format!("function {} has {} arguments, expected {}", name, ffi.args.len(), generated.args.len()));
+    return problems;
+  }
+  for (i, (a, b)) in ffi.args.iter().zip(generated.args.iter()).enumerate() {
+    let relaxed = CONST_ARGUMENTS.contains(&(name, i)) && a.replace("*const", "*mut") == b.replace("*const", "*mut");
+    if a != b && !relaxed {
+      problems.push(format!("function {} argument {} is {}, expected {}", name, i, a, b));
+    }
+  }
+  if ffi.ret != generated.ret {
+    problems.push(format!("function {} returns {}, expected {}", name, ffi.ret, generated.ret));
+  }
+  problems
+}
+
+#[cfg(feature = "generate-bindings")]
+#[test]
+fn ffi_matches_generated_bindings() {
+  let generated_fns = functions(ovr::raw::SOURCE);
+  let generated_structs = structs(ovr::raw::SOURCE);
+  let ffi_fns = functions(FFI_SOURCE);
+  let ffi_structs = structs(FFI_SOURCE);
+
+  let mut problems = Vec::new();
+
+  for (name, signature) in generated_fns.iter() {
+    if IGNORED_FUNCTIONS.contains(&name.as_slice()) {
+      continue;
+    }
+    match ffi_fns.get(name) {
+      None => problems.push(format!("missing function {}", name)),
+      Some(ffi) => problems.extend(compare_signatures(name.as_slice(), ffi, signature).into_iter()),
+    }
+  }
+
+  for (name, fields) in generated_structs.iter() {
+    // the GL specific structs are passed through the generic PlatformData arrays
+    if name.starts_with("GL") {
+      continue;
+    }
+    match ffi_structs.get(name) {
+      None => problems.push(format!("missing struct {}", name)),
+      Some(ffi) if ffi.len() != fields.len() =>
+        problems.push(format!("struct {} has {} fields, expected {}", name, ffi.len(), fields.len())),
+      Some(ffi) => {
+        for (i, (a, b)) in ffi.iter().zip(fields.iter()).enumerate() {
+          if a != b {
+            problems.push(format!("struct {} field {} is {}, expected {}", name, i, a, b));
+          }
+        }
+      }
+    }
+  }
+
+  problems.sort();
+  assert!(problems.is_empty(), "ffi.rs differs from the generated bindings in {} places:\n{}",
+          problems.len(), problems.connect("\n"));
+}
@@ -0,0 +1,66 @@
+//! Round trips through the `cgmath` conversions, run with
+//! `cargo test --features cgmath`.
+
+#![cfg(feature = "cgmath")]
+
+extern crate cgmath;
+extern crate "libovr-rs" as ovr;
+
+use cgmath::{Vector3, Vector4, Quaternion, Matrix4, Perspective};
+
+use ovr::convert::{ToMath, ToOvr};
+use ovr::cgmath_conv::{Isometry, fov_to_perspective};
+use ovr::ffi::{Vector3f, Quatf, Matrix4f, Posef, FovPort};
+
+#[test]
+fn vector_and_quaternion_round_trip() {
+  let v = Vector3f::new(1.0, 2.0, 3.0);
+  let m: Vector3<f32> = v.to_math();
+  assert_eq!(m, Vector3::new(1.0, 2.0, 3.0));
+  let back: Vector3f = m.to_ovr();
+  assert_eq!((back.x, back.y, back.z), (1.0, 2.0, 3.0));
+
+  let q = Quatf::new(0.1, 0.2, 0.3, 0.9);
+  let m: Quaternion<f32> = q.to_math();
+  assert_eq!((m.s, m.v.x, m.v.y, m.v.z), (0.9, 0.1, 0.2, 0.3));
+  let back: Quatf = m.to_ovr();
+  assert_eq!((back.x, back.y, back.z, back.w), (0.1, 0.2, 0.3, 0.9));
+}
+
+#[test]
+fn matrix_round_trip_transposes() {
+  let mut m = Matrix4f::identity();
+  for r in range(0u, 4) {
+    for c in range(0u, 4) {
+      m.M[r][c] = (r * 4 + c) as f32;
+    }
+  }
+  let math: Matrix4<f32> = m.to_math();
+  // cgmath stores columns, so row 1 of M is the y component of every column
+  assert_eq!(math.x, Vector4::new(0.0, 4.0, 8.0, 12.0));
+  assert_eq!(math.y.x, 1.0);
+  let back: Matrix4f = math.to_ovr();
+  assert_eq!(back.M, m.M);
+}
+
+#[test]
+fn translation_ends_up_in_the_last_column() {
+  let pose = Posef::new(Quatf::new(0.0, 0.0, 0.0, 1.0), Vector3f::new(1.0, 2.0, 3.0));
+  let math: Matrix4<f32> = Matrix4f::from_pose(&pose).to_math();
+  assert_eq!(math.w, Vector4::new(1.0, 2.0, 3.0, 1.0));
+
+  let iso: Isometry = pose.to_math();
+  assert_eq!(iso.disp, Vector3::new(1.0, 2.0, 3.0));
+  let back: Posef = iso.to_ovr();
+  assert_eq!((back.Position.x, back.Position.y, back.Position.z), (1.0, 2.0, 3.0));
+  assert_eq!(back.Orientation.w, 1.0);
+}
+
+#[test]
+fn fov_round_trip() {
+  let fov = FovPort{ UpTan: 1.0, DownTan: 1.25, LeftTan: 0.5, RightTan: 0.75 };
+  let p: Perspective<f32> = fov_to_perspective(&fov, 0.5, 10.0);
+  assert_eq!((p.left, p.right, p.bottom, p.top), (-0.25, 0.375, -0.625, 0.5));
+  let back: FovPort = p.to_ovr();
+  assert_eq!((back.UpTan, back.DownTan, back.LeftTan, back.RightTan), (1.0, 1.25, 0.5, 0.75));
+}
@@ -0,0 +1,71 @@
+//! Checks the CPU distortion renderer with a hand made mesh, and the PPM
+//! reading and writing used for comparing its output.
+
+extern crate "libovr-rs" as ovr;
+
+use std::io::{BufReader, MemWriter};
+
+use ovr::{EyeL, BitMask};
+use ovr::distortion::{mod, Image, Mesh, EyeDistortion};
+use ovr::ffi::{DistortionVertex, FovPort, Vector2f};
+
+/// Colored grid lines on a gradient, different per eye.
+fn test_pattern(width: uint, height: uint) -> Image {
+  let mut image = Image::new(width, height);
+  for y in range(0, height) {
+    for x in range(0, width) {
+      let rgb = if x % 32 == 0 || y % 32 == 0 {
+        [255, 255, 255]
+      } else if x < width / 2 {
+        [(x * 255 / width) as u8, (y * 255 / height) as u8, 64]
+      } else {
+        [64, (y * 255 / height) as u8, (x * 255 / width) as u8]
+      };
+      image.set(x, y, rgb);
+    }
+  }
+  image
+}
+
+fn vertex(x: f32, y: f32, tan_x: f32, tan_y: f32) -> DistortionVertex {
+  let tan = Vector2f{ x: tan_x, y: tan_y };
+  DistortionVertex{
+    ScreenPosNDC: Vector2f{ x: x, y: y },
+    TimeWarpFactor: 0.0,
+    VignetteFactor: 1.0,
+    TanEyeAnglesR: tan.clone(),
+    TanEyeAnglesG: tan.clone(),
+    TanEyeAnglesB: tan,
+  }
+}
+
+#[test]
+fn undistorted_mesh_copies_the_left_eye() {
+  // covers the left half of the screen, tan angles with y pointing down
+  let mesh = Mesh{
+    vertices: vec![vertex(-1.0, 1.0, -1.0, -1.0), vertex(0.0, 1.0, 1.0, -1.0),
+                   vertex(-1.0, -1.0, -1.0, 1.0), vertex(0.0, -1.0, 1.0, 1.0)],
+    indices: vec![0, 1, 2, 2, 1, 3],
+  };
+  let fov = FovPort{ UpTan: 1.0, DownTan: 1.0, LeftTan: 1.0, RightTan: 1.0 };
+  let source = test_pattern(16, 8);
+  let eye = EyeDistortion::new(mesh, EyeL, &fov, source.width, source.height);
+  let caps = BitMask{ mask: ovr::ffi::DistortionCap_Chromatic | ovr::ffi::DistortionCap_Vignette };
+
+  let output = distortion::render(&source, &[eye], caps, 16, 8);
+  for y in range(0, 8) {
+    for x in range(0, 16) {
+      let expected = if x < 8 { source.get(x, y) } else { [0, 0, 0] };
+      assert_eq!((x, y, output.get(x, y)), (x, y, expected));
+    }
+  }
+}
+
+#[test]
+fn ppm_round_trip() {
+  let image = test_pattern(7, 5);
+  let mut writer = MemWriter::new();
+  image.write_ppm(&mut writer).unwrap();
+  let read = Image::read_ppm(&mut BufReader::new(writer.get_ref())).unwrap();
+  assert!(read == image);
+}
@@ -0,0 +1,192 @@
+//! Exports two hand-built samples and checks the exact CSV and JSON Lines text.
+
+extern crate "libovr-rs" as ovr;
+
+use std::default::Default;
+use std::io::MemWriter;
+use std::f64;
+
+use ovr::export::{Orientation, OrientationQuaternion, OrientationEuler, CsvExporter, JsonLinesExporter};
+use ovr::ffi;
+use ovr::ffi::{TrackingState, Posef, Quatf, Vector3f};
+
+static CSV_QUATERNION: &'static str = "\
+time,head_orientation_x,head_orientation_y,head_orientation_z,head_orientation_w,\
+head_position_x,head_position_y,head_position_z,head_angular_velocity_x,head_angular_velocity_y,\
+head_angular_velocity_z,head_linear_velocity_x,head_linear_velocity_y,head_linear_velocity_z,\
+head_angular_acceleration_x,head_angular_acceleration_y,head_angular_acceleration_z,\
+head_linear_acceleration_x,head_linear_acceleration_y,head_linear_acceleration_z,\
+camera_orientation_x,camera_orientation_y,camera_orientation_z,camera_orientation_w,\
+camera_position_x,camera_position_y,camera_position_z,leveled_camera_orientation_x,\
+leveled_camera_orientation_y,leveled_camera_orientation_z,leveled_camera_orientation_w,\
+leveled_camera_position_x,leveled_camera_position_y,leveled_camera_position_z,accelerometer_x,\
+accelerometer_y,accelerometer_z,gyro_x,gyro_y,gyro_z,magnetometer_x,magnetometer_y,\
+magnetometer_z,temperature,sensor_time,orientation_tracked,position_tracked,camera_pose_tracked,\
+position_connected,hmd_connected,last_vision_processing_time,last_vision_frame_latency,\
+last_camera_frame_counter\n\
+1.5,0,0,0,1,0.5,1.5,-2,0,0.25,0,1,0,0,0,0,0,0,0,0,0,0,0,1,0,0,-1,0,0,0,1,0,0,-1,0,9.75,0,0,0.25,\
+0,0.5,0,0,30,1.5,1,0,0,0,1,0.125,0.0625,42\n\
+2,0,0,0,1,0.5,1.5,-2,0,0.25,0,1,0,0,0,0,0,0,0,0,0,0,0,1,0,0,-1,0,0,0,1,0,0,-1,0,9.75,0,0,0.25,0,\
+0.5,0,0,30,2,1,1,1,1,1,0.125,,43\n";
+
+static JSON_LINES_QUATERNION: &'static str = "\
+{\"time\":1.5,\"head_orientation_x\":0,\"head_orientation_y\":0,\"head_orientation_z\":0,\
+\"head_orientation_w\":1,\"head_position_x\":0.5,\"head_position_y\":1.5,\"head_position_z\":-2,\
+\"head_angular_velocity_x\":0,\"head_angular_velocity_y\":0.25,\"head_angular_velocity_z\":0,\
+\"head_linear_velocity_x\":1,\"head_linear_velocity_y\":0,\"head_linear_velocity_z\":0,\
+\"head_angular_acceleration_x\":0,\"head_angular_acceleration_y\":0,\"head_angular_acceleration_z\":0,\
+\"head_linear_acceleration_x\":0,\"head_linear_acceleration_y\":0,\"head_linear_acceleration_z\":0,\
+\"camera_orientation_x\":0,\"camera_orientation_y\":0,\"camera_orientation_z\":0,\
+\"camera_orientation_w\":1,\"camera_position_x\":0,\"camera_position_y\":0,\"camera_position_z\":-1,\
+\"leveled_camera_orientation_x\":0,\"leveled_camera_orientation_y\":0,\
+\"leveled_camera_orientation_z\":0,\"leveled_camera_orientation_w\":1,\"leveled_camera_position_x\":0,\
+\"leveled_camera_position_y\":0,\"leveled_camera_position_z\":-1,\"accelerometer_x\":0,\
+\"accelerometer_y\":9.75,\"accelerometer_z\":0,\"gyro_x\":0,\"gyro_y\":0.25,\"gyro_z\":0,\
+\"magnetometer_x\":0.5,\"magnetometer_y\":0,\"magnetometer_z\":0,\"temperature\":30,\"sensor_time\":1.5,\
+\"orientation_tracked\":true,\"position_tracked\":false,\"camera_pose_tracked\":false,\
+\"position_connected\":false,\"hmd_connected\":true,\"last_vision_processing_time\":0.125,\
+\"last_vision_frame_latency\":0.0625,\"last_camera_frame_counter\":42}\n\
+{\"time\":2,\"head_orientation_x\":0,\"head_orientation_y\":0,\"head_orientation_z\":0,\
+\"head_orientation_w\":1,\"head_position_x\":0.5,\"head_position_y\":1.5,\"head_position_z\":-2,\
+\"head_angular_velocity_x\":0,\"head_angular_velocity_y\":0.25,\"head_angular_velocity_z\":0,\
+\"head_linear_velocity_x\":1,\"head_linear_velocity_y\":0,\"head_linear_velocity_z\":0,\
+\"head_angular_acceleration_x\":0,\"head_angular_acceleration_y\":0,\"head_angular_acceleration_z\":0,\
+\"head_linear_acceleration_x\":0,\"head_linear_acceleration_y\":0,\"head_linear_acceleration_z\":0,\
+\"camera_orientation_x\":0,\"camera_orientation_y\":0,\"camera_orientation_z\":0,\
+\"camera_orientation_w\":1,\"camera_position_x\":0,\"camera_position_y\":0,\"camera_position_z\":-1,\
+\"leveled_camera_orientation_x\":0,\"leveled_camera_orientation_y\":0,\
+\"leveled_camera_orientation_z\":0,\"leveled_camera_orientation_w\":1,\"leveled_camera_position_x\":0,\
+\"leveled_camera_position_y\":0,\"leveled_camera_position_z\":-1,\"accelerometer_x\":0,\
+\"accelerometer_y\":9.75,\"accelerometer_z\":0,\"gyro_x\":0,\"gyro_y\":0.25,\"gyro_z\":0,\
+\"magnetometer_x\":0.5,\"magnetometer_y\":0,\"magnetometer_z\":0,\"temperature\":30,\"sensor_time\":2,\
+\"orientation_tracked\":true,\"position_tracked\":true,\"camera_pose_tracked\":true,\
+\"position_connected\":true,\"hmd_connected\":true,\"last_vision_processing_time\":0.125,\
+\"last_vision_frame_latency\":null,\"last_camera_frame_counter\":43}\n";
+
+// `<yaw>`, `<pitch>` and `<roll>` stand for the Euler angles of the identity
+// orientation, see `euler`.
+static CSV_EULER: &'static str = "\
+time,head_yaw,head_pitch,head_roll,head_position_x,head_position_y,head_position_z,\
+head_angular_velocity_x,head_angular_velocity_y,head_angular_velocity_z,head_linear_velocity_x,\
+head_linear_velocity_y,head_linear_velocity_z,head_angular_acceleration_x,\
+head_angular_acceleration_y,head_angular_acceleration_z,head_linear_acceleration_x,\
+head_linear_acceleration_y,head_linear_acceleration_z,camera_yaw,camera_pitch,camera_roll,\
+camera_position_x,camera_position_y,camera_position_z,leveled_camera_yaw,leveled_camera_pitch,\
+leveled_camera_roll,leveled_camera_position_x,leveled_camera_position_y,\
+leveled_camera_position_z,accelerometer_x,accelerometer_y,accelerometer_z,gyro_x,gyro_y,gyro_z,\
+magnetometer_x,magnetometer_y,magnetometer_z,temperature,sensor_time,orientation_tracked,\
+position_tracked,camera_pose_tracked,position_connected,hmd_connected,\
+last_vision_processing_time,last_vision_frame_latency,last_camera_frame_counter\n\
+1.5,<yaw>,<pitch>,<roll>,0.5,1.5,-2,0,0.25,0,1,0,0,0,0,0,0,0,0,<yaw>,<pitch>,<roll>,0,0,-1,\
+<yaw>,<pitch>,<roll>,0,0,-1,0,9.75,0,0,0.25,0,0.5,0,0,30,1.5,1,0,0,0,1,0.125,0.0625,42\n\
+2,<yaw>,<pitch>,<roll>,0.5,1.5,-2,0,0.25,0,1,0,0,0,0,0,0,0,0,<yaw>,<pitch>,<roll>,0,0,-1,<yaw>,\
+<pitch>,<roll>,0,0,-1,0,9.75,0,0,0.25,0,0.5,0,0,30,2,1,1,1,1,1,0.125,,43\n";
+
+static JSON_LINES_EULER: &'static str = "\
+{\"time\":1.5,\"head_yaw\":<yaw>,\"head_pitch\":<pitch>,\"head_roll\":<roll>,\"head_position_x\":0.5,\
+\"head_position_y\":1.5,\"head_position_z\":-2,\"head_angular_velocity_x\":0,\
+\"head_angular_velocity_y\":0.25,\"head_angular_velocity_z\":0,\"head_linear_velocity_x\":1,\
+\"head_linear_velocity_y\":0,\"head_linear_velocity_z\":0,\"head_angular_acceleration_x\":0,\
+\"head_angular_acceleration_y\":0,\"head_angular_acceleration_z\":0,\"head_linear_acceleration_x\":0,\
+\"head_linear_acceleration_y\":0,\"head_linear_acceleration_z\":0,\"camera_yaw\":<yaw>,\
+\"camera_pitch\":<pitch>,\"camera_roll\":<roll>,\"camera_position_x\":0,\"camera_position_y\":0,\
+\"camera_position_z\":-1,\"leveled_camera_yaw\":<yaw>,\"leveled_camera_pitch\":<pitch>,\
+\"leveled_camera_roll\":<roll>,\"leveled_camera_position_x\":0,\"leveled_camera_position_y\":0,\
+\"leveled_camera_position_z\":-1,\"accelerometer_x\":0,\"accelerometer_y\":9.75,\"accelerometer_z\":0,\
+\"gyro_x\":0,\"gyro_y\":0.25,\"gyro_z\":0,\"magnetometer_x\":0.5,\"magnetometer_y\":0,\"magnetometer_z\":0,\
+\"temperature\":30,\"sensor_time\":1.5,\"orientation_tracked\":true,\"position_tracked\":false,\
+\"camera_pose_tracked\":false,\"position_connected\":false,\"hmd_connected\":true,\
+\"last_vision_processing_time\":0.125,\"last_vision_frame_latency\":0.0625,\
+\"last_camera_frame_counter\":42}\n\
+{\"time\":2,\"head_yaw\":<yaw>,\"head_pitch\":<pitch>,\"head_roll\":<roll>,\"head_position_x\":0.5,\
+\"head_position_y\":1.5,\"head_position_z\":-2,\"head_angular_velocity_x\":0,\
+\"head_angular_velocity_y\":0.25,\"head_angular_velocity_z\":0,\"head_linear_velocity_x\":1,\
+\"head_linear_velocity_y\":0,\"head_linear_velocity_z\":0,\"head_angular_acceleration_x\":0,\
+\"head_angular_acceleration_y\":0,\"head_angular_acceleration_z\":0,\"head_linear_acceleration_x\":0,\
+\"head_linear_acceleration_y\":0,\"head_linear_acceleration_z\":0,\"camera_yaw\":<yaw>,\
+\"camera_pitch\":<pitch>,\"camera_roll\":<roll>,\"camera_position_x\":0,\"camera_position_y\":0,\
+\"camera_position_z\":-1,\"leveled_camera_yaw\":<yaw>,\"leveled_camera_pitch\":<pitch>,\
+\"leveled_camera_roll\":<roll>,\"leveled_camera_position_x\":0,\"leveled_camera_position_y\":0,\
+\"leveled_camera_position_z\":-1,\"accelerometer_x\":0,\"accelerometer_y\":9.75,\"accelerometer_z\":0,\
+\"gyro_x\":0,\"gyro_y\":0.25,\"gyro_z\":0,\"magnetometer_x\":0.5,\"magnetometer_y\":0,\"magnetometer_z\":0,\
+\"temperature\":30,\"sensor_time\":2,\"orientation_tracked\":true,\"position_tracked\":true,\
+\"camera_pose_tracked\":true,\"position_connected\":true,\"hmd_connected\":true,\
+\"last_vision_processing_time\":0.125,\"last_vision_frame_latency\":null,\
+\"last_camera_frame_counter\":43}\n";
+
+/// The first sample only tracks orientation; the second has every status bit
+/// set and no vision frame latency.
+fn samples() -> [TrackingState, ..2] {
+  let mut a: TrackingState = Default::default();
+  a.HeadPose.ThePose = Posef::new(Quatf::identity(), Vector3f::new(0.5, 1.5, -2.0));
+  a.HeadPose.AngularVelocity = Vector3f::new(0.0, 0.25, 0.0);
+  a.HeadPose.LinearVelocity = Vector3f::new(1.0, 0.0, 0.0);
+  a.HeadPose.TimeInSeconds = 1.5;
+  a.CameraPose = Posef::new(Quatf::identity(), Vector3f::new(0.0, 0.0, -1.0));
+  a.LeveledCameraPose = a.CameraPose.clone();
+  a.RawSensorData.Accelerometer = Vector3f::new(0.0, 9.75, 0.0);
+  a.RawSensorData.Gyro = Vector3f::new(0.0, 0.25, 0.0);
+  a.RawSensorData.Magnetometer = Vector3f::new(0.5, 0.0, 0.0);
+  a.RawSensorData.Temperature = 30.0;
+  a.RawSensorData.TimeInSeconds = 1.5;
+  a.StatusFlags = ffi::Status_OrientationTracked | ffi::Status_HmdConnected;
+  a.LastVisionProcessingTime = 0.125;
+  a.LastVisionFrameLatency = 0.0625;
+  a.LastCameraFrameCounter = 42;
+
+  let mut b = a.clone();
+  b.HeadPose.TimeInSeconds = 2.0;
+  b.RawSensorData.TimeInSeconds = 2.0;
+  b.StatusFlags = ffi::Status_OrientationTracked | ffi::Status_PositionTracked | ffi::Status_CameraPoseTracked |
+                  ffi::Status_PositionConnected | ffi::Status_HmdConnected;
+  b.LastVisionFrameLatency = f64::NAN;
+  b.LastCameraFrameCounter = 43;
+  [a, b]
+}
+
+/// Fills in the Euler angles of the identity orientation. The exporter
+/// formats them as `f64`, so pitch may come out as `-0`.
+fn euler(text: &str) -> String {
+  let (yaw, pitch, roll) = Quatf::identity().to_euler_yxz();
+  text.replace("<yaw>", format!("{}", yaw as f64).as_slice())
+      .replace("<pitch>", format!("{}", pitch as f64).as_slice())
+      .replace("<roll>", format!("{}", roll as f64).as_slice())
+}
+
+fn csv(orientation: Orientation) -> String {
+  let mut exporter = CsvExporter::new(MemWriter::new(), orientation).unwrap();
+  for s in samples().iter() {
+    exporter.write_sample(s).unwrap();
+  }
+  assert_eq!(exporter.samples(), 2);
+  String::from_utf8(exporter.into_inner().get_ref().to_vec()).unwrap()
+}
+
+fn json_lines(orientation: Orientation) -> String {
+  let mut exporter = JsonLinesExporter::new(MemWriter::new(), orientation);
+  for s in samples().iter() {
+    exporter.write_sample(s).unwrap();
+  }
+  assert_eq!(exporter.samples(), 2);
+  String::from_utf8(exporter.into_inner().get_ref().to_vec()).unwrap()
+}
+
+#[test]
+fn csv_with_quaternions() {
+  assert_eq!(csv(OrientationQuaternion).as_slice(), CSV_QUATERNION);
+}
+
+#[test]
+fn csv_with_euler_angles() {
+  assert_eq!(csv(OrientationEuler), euler(CSV_EULER));
+}
+
+#[test]
+fn json_lines_with_quaternions() {
+  assert_eq!(json_lines(OrientationQuaternion).as_slice(), JSON_LINES_QUATERNION);
+}
+
+#[test]
+fn json_lines_with_euler_angles() {
+  assert_eq!(json_lines(OrientationEuler), euler(JSON_LINES_EULER));
+}
@@ -0,0 +1,132 @@
+//! Feeds hand-built head positions through the tracking filters.
+
+extern crate "libovr-rs" as ovr;
+
+use std::default::Default;
+use std::num::Float;
+
+use ovr::filter::{TrackingFilter, ExponentialFilter, OneEuroFilter, OneEuroParams, HoldAndBlend};
+use ovr::ffi;
+use ovr::ffi::{TrackingState, Quatf, Vector3f};
+
+/// A sample at `time` with the head at `x` meters along X and turned by
+/// `x` / 10 radians.
+fn sample(time: f64, x: f32, tracked: bool) -> TrackingState {
+  let mut state: TrackingState = Default::default();
+  state.HeadPose.TimeInSeconds = time;
+  state.HeadPose.ThePose.Position = Vector3f::new(x, 0.0, 0.0);
+  state.HeadPose.ThePose.Orientation = Quatf::from_axis_angle(&Vector3f::new(0.0, 1.0, 0.0), 0.1 * x);
+  state.StatusFlags = ffi::Status_OrientationTracked |
+                      (if tracked { ffi::Status_PositionTracked } else { 0 });
+  state
+}
+
+fn x_of(state: &TrackingState) -> f32 {
+  state.HeadPose.ThePose.Position.x
+}
+
+fn assert_x(state: &TrackingState, x: f32) {
+  assert!((x_of(state) - x).abs() < 1e-5, "x is {} at {}, expected {}", x_of(state), state.HeadPose.TimeInSeconds, x);
+}
+
+fn one_euro_params() -> OneEuroParams {
+  OneEuroParams{ min_cutoff: 1.0, beta: 0.5, derivative_cutoff: 1.0 }
+}
+
+#[test]
+fn hold_and_blend_holds_while_untracked() {
+  let mut filter = HoldAndBlend::new(1.0);
+  assert_x(&filter.filter(&sample(0.0, 0.0, true)), 0.0);
+  assert_x(&filter.filter(&sample(0.1, 1.0, true)), 1.0);
+  assert!(!filter.is_holding());
+
+  for &(time, x) in [(0.2, 5.0), (0.3, 6.0)].iter() {
+    let held = filter.filter(&sample(time, x, false));
+    assert!(filter.is_holding());
+    assert_x(&held, 1.0);
+    // the orientation and time are passed through
+    assert!((held.HeadPose.ThePose.Orientation.yaw() - 0.1 * x).abs() < 1e-5);
+    assert_eq!(held.HeadPose.TimeInSeconds, time);
+  }
+}
+
+#[test]
+fn hold_and_blend_blends_back_linearly() {
+  let mut filter = HoldAndBlend::new(1.0);
+  filter.filter(&sample(0.0, 1.0, true));
+  filter.filter(&sample(0.5, 4.0, false));
+
+  // tracking comes back two meters away and is reached after `blend_time`
+  assert_x(&filter.filter(&sample(1.0, 3.0, true)), 1.0);
+  assert!(!filter.is_holding());
+  assert_x(&filter.filter(&sample(1.25, 3.0, true)), 1.5);
+  assert_x(&filter.filter(&sample(1.5, 3.0, true)), 2.0);
+  assert_x(&filter.filter(&sample(1.75, 3.5, true)), 3.0);
+  assert_x(&filter.filter(&sample(2.0, 3.0, true)), 3.0);
+  assert_x(&filter.filter(&sample(2.5, 4.0, true)), 4.0);
+}
+
+#[test]
+fn hold_and_blend_without_blend_time_jumps() {
+  let mut filter = HoldAndBlend::new(0.0);
+  filter.filter(&sample(0.0, 1.0, true));
+  assert_x(&filter.filter(&sample(0.5, 4.0, false)), 1.0);
+  assert_x(&filter.filter(&sample(1.0, 3.0, true)), 3.0);
+}
+
+#[test]
+fn zero_time_constants_pass_samples_through() {
+  let mut filter = ExponentialFilter::new(0.0, 0.0);
+  for i in range(0u, 5) {
+    let x = (i * i) as f32;
+    let filtered = filter.filter(&sample(i as f64 * 0.1, x, true));
+    assert_x(&filtered, x);
+    assert!((filtered.HeadPose.ThePose.Orientation.yaw() - 0.1 * x).abs() < 1e-5);
+  }
+
+  // only the position is smoothed
+  let mut filter = ExponentialFilter::new(1.0, 0.0);
+  filter.filter(&sample(0.0, 0.0, true));
+  let filtered = filter.filter(&sample(0.1, 1.0, true));
+  let expected = 1.0 - (-0.1f64).exp();
+  assert_x(&filtered, expected as f32);
+  assert!((filtered.HeadPose.ThePose.Orientation.yaw() - 0.1).abs() < 1e-5);
+}
+
+#[test]
+fn reset_forgets_history() {
+  let mut exponential = ExponentialFilter::new(1.0, 1.0);
+  exponential.filter(&sample(0.0, 0.0, true));
+  assert!(x_of(&exponential.filter(&sample(0.1, 1.0, true))) < 0.5);
+  exponential.reset();
+  assert_x(&exponential.filter(&sample(0.2, 5.0, true)), 5.0);
+
+  let mut one_euro = OneEuroFilter::new(one_euro_params(), one_euro_params());
+  one_euro.filter(&sample(0.0, 0.0, true));
+  assert!(x_of(&one_euro.filter(&sample(0.1, 1.0, true))) < 1.0);
+  one_euro.reset();
+  assert_x(&one_euro.filter(&sample(0.2, 5.0, true)), 5.0);
+
+  let mut hold = HoldAndBlend::new(1.0);
+  hold.filter(&sample(0.0, 1.0, true));
+  assert_x(&hold.filter(&sample(0.1, 2.0, false)), 1.0);
+  hold.reset();
+  assert!(!hold.is_holding());
+  // nothing is held, so an untracked sample passes through
+  assert_x(&hold.filter(&sample(0.2, 2.0, false)), 2.0);
+}
+
+#[test]
+fn one_euro_keeps_the_output_for_repeated_timestamps() {
+  let mut filter = OneEuroFilter::new(one_euro_params(), one_euro_params());
+  filter.filter(&sample(0.0, 0.0, true));
+  let first = filter.filter(&sample(0.1, 1.0, true));
+  let repeated = filter.filter(&sample(0.1, 7.0, true));
+  assert_eq!(x_of(&repeated), x_of(&first));
+  let (a, b) = (&first.HeadPose.ThePose.Orientation, &repeated.HeadPose.ThePose.Orientation);
+  assert_eq!((a.x, a.y, a.z, a.w), (b.x, b.y, b.z, b.w));
+
+  // and continues from it with the next sample
+  let next = filter.filter(&sample(0.2, 1.0, true));
+  assert!(x_of(&next) > x_of(&first) && x_of(&next) < 1.0);
+}
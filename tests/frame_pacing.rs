@@ -0,0 +1,45 @@
+//! Runs `FramePacer` against a mock DK2 on a simulated clock.
+
+extern crate "libovr-rs" as ovr;
+
+use ovr::HmdDK2;
+use ovr::mock::{MockHmd, Still};
+use ovr::clock::{Clock, SimulatedClock};
+use ovr::frame_pacer::FramePacer;
+
+#[test]
+fn samples_pose_at_lead_time_before_timewarp() {
+  let hmd = MockHmd::new(HmdDK2, Still);
+  let mut pacer = FramePacer::new(SimulatedClock::new(0.0), 0.004);
+
+  let timing = pacer.begin_frame(&hmd);
+  let state = pacer.wait_for_pose(&hmd);
+  assert_eq!(pacer.clock().now(), timing.TimewarpPointSeconds - 0.004);
+  assert_eq!(state.HeadPose.TimeInSeconds, timing.ScanoutMidpointSeconds);
+
+  pacer.clock().advance(0.002);
+  assert_eq!(pacer.end_frame(&hmd), None);
+  assert_eq!(pacer.frame_index(), 1);
+}
+
+#[test]
+fn reports_frames_that_miss_the_timewarp_point() {
+  let hmd = MockHmd::new(HmdDK2, Still);
+  let interval = hmd.frame_interval();
+  let mut pacer = FramePacer::new(SimulatedClock::new(0.0), 0.004);
+
+  for frame in range(0i32, 6) {
+    pacer.begin_frame(&hmd);
+    pacer.wait_for_pose(&hmd);
+    // the last frame takes one and a half vsync intervals to render
+    pacer.clock().advance(if frame == 5 { interval * 1.5 } else { 0.003 });
+    let dropped = pacer.end_frame(&hmd);
+    assert_eq!(dropped.is_some(), frame == 5);
+    if let Some(dropped) = dropped {
+      assert_eq!(dropped.frame_index, 5);
+      assert_eq!(dropped.missed_vsyncs, 2);
+    }
+  }
+  assert_eq!(pacer.frames(), 6);
+  assert_eq!(pacer.dropped_frames(), 1);
+}
@@ -0,0 +1,95 @@
+//! Computes frame statistics over a hand-built sequence of frame timings.
+
+extern crate "libovr-rs" as ovr;
+
+use std::num::Float;
+
+use ovr::frame_stats::FrameStats;
+use ovr::ffi::FrameTiming;
+
+/// The vsync interval of a 64 Hz display, exact in binary.
+const INTERVAL: f64 = 1.0 / 64.0;
+
+/// Frame deltas in seconds. The first frame after starting reports zero;
+/// the frames taking two and three intervals miss one and two vsyncs.
+static DELTAS: [f32, ..10] = [0.0, 0.015625, 0.0156, 0.0157, 0.03125, 0.015, 0.046875, 0.0155, 0.0159, 0.016];
+
+/// Timings for `DELTAS`, with scanout one and a half intervals after the
+/// start of each frame.
+fn timings() -> Vec<FrameTiming> {
+  let mut this = 100.0;
+  DELTAS.iter().map(|&delta| {
+    this += delta as f64;
+    FrameTiming{
+      DeltaSeconds: delta,
+      ThisFrameSeconds: this,
+      TimewarpPointSeconds: this + 0.75 * INTERVAL,
+      NextFrameSeconds: this + INTERVAL,
+      ScanoutMidpointSeconds: this + 1.5 * INTERVAL,
+      EyeScanoutSeconds: [this + 1.25 * INTERVAL, this + 1.75 * INTERVAL],
+    }
+  }).collect()
+}
+
+/// Adds every frame, with the pose sampled at the start of all but the first.
+fn add_all(stats: &mut FrameStats) {
+  for (i, timing) in timings().iter().enumerate() {
+    let pose_sample_time = if i == 0 { None } else { Some(timing.ThisFrameSeconds) };
+    stats.add_frame(timing, pose_sample_time);
+  }
+}
+
+#[test]
+fn frame_time_percentiles() {
+  let mut stats = FrameStats::new(16);
+  add_all(&mut stats);
+
+  // the zero delta of the first frame is left out; sorted, the frame times are
+  // 0.015, 0.0155, 0.0156, 0.015625, 0.0157, 0.0159, 0.016, 0.03125, 0.046875
+  let summary = stats.frame_times().unwrap();
+  assert_eq!(summary.count, 9);
+  assert_eq!(summary.min, 0.015f32 as f64);
+  assert_eq!(summary.max, 0.046875);
+  assert_eq!(summary.p50, 0.0157f32 as f64);
+  assert_eq!(summary.p95, 0.046875);
+  assert_eq!(summary.p99, 0.046875);
+  let sum = DELTAS.iter().fold(0.0, |acc, d| acc + *d as f64);
+  assert!((summary.mean - sum / 9.0).abs() < 1e-12);
+  assert!((stats.fps().unwrap() - 9.0 / sum).abs() < 1e-9);
+
+  // nearest rank
+  assert_eq!(stats.frame_time_percentile(0.0), Some(0.015f32 as f64));
+  assert_eq!(stats.frame_time_percentile(10.0), Some(0.015f32 as f64));
+  assert_eq!(stats.frame_time_percentile(12.0), Some(0.0155f32 as f64));
+  assert_eq!(stats.frame_time_percentile(70.0), Some(0.016f32 as f64));
+  assert_eq!(stats.frame_time_percentile(80.0), Some(0.03125));
+  assert_eq!(stats.frame_time_percentile(90.0), Some(0.046875));
+  assert_eq!(stats.frame_time_percentile(100.0), Some(0.046875));
+
+  let latency = stats.photon_latencies().unwrap();
+  assert_eq!(latency.count, 9);
+  assert!((latency.p50 - 1.5 * INTERVAL).abs() < 1e-9);
+  assert!((stats.refresh_rate().unwrap() - 64.0).abs() < 1e-6);
+}
+
+#[test]
+fn missed_vsyncs_in_window_and_total() {
+  let mut stats = FrameStats::new(16);
+  add_all(&mut stats);
+  assert_eq!(stats.total_frames(), 10);
+  assert_eq!(stats.missed_vsyncs(), 3);
+  assert_eq!(stats.total_missed_vsyncs(), 3);
+
+  // only the last four frames, with the one missing two vsyncs, stay in the window
+  let mut windowed = FrameStats::new(4);
+  add_all(&mut windowed);
+  assert_eq!(windowed.total_frames(), 10);
+  assert_eq!(windowed.missed_vsyncs(), 2);
+  assert_eq!(windowed.total_missed_vsyncs(), 3);
+  assert_eq!(windowed.frame_times().unwrap().count, 4);
+
+  windowed.reset();
+  assert_eq!((windowed.total_frames(), windowed.missed_vsyncs(), windowed.total_missed_vsyncs()), (0, 0, 0));
+  assert!(windowed.frame_times().is_none());
+  assert!(windowed.fps().is_none());
+}
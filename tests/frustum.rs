@@ -0,0 +1,63 @@
+//! Checks the planes extracted from SDK projection matrices and the
+//! combined stereo frustum of a debug DK2.
+
+extern crate "libovr-rs" as ovr;
+
+use std::sync::{StaticMutex, MUTEX_INIT};
+
+use ovr::{Ovr, HmdDK2, EyeL, EyeR};
+use ovr::ffi::{FovPort, Vector3f};
+use ovr::frustum::{mod, Frustum};
+
+/// The SDK must not be initialized twice at a time.
+static SDK: StaticMutex = MUTEX_INIT;
+
+/// Like `Frustum::contains_point`, with a tolerance for points on a plane.
+fn contains(frustum: &Frustum, p: &Vector3f) -> bool {
+  frustum.planes.iter().all(|plane| plane.signed_distance(p) >= -1e-4)
+}
+
+#[test]
+fn projection_planes_bound_the_view_volume() {
+  let _guard = SDK.lock();
+  let _sdk = Ovr::initialize().unwrap();
+  let fov = FovPort{ UpTan: 1.0, DownTan: 1.0, LeftTan: 1.0, RightTan: 1.0 };
+  let f = Frustum::from_projection(&Ovr::matrix_projection(fov, 0.1, 100.0, true));
+
+  assert!(f.contains_point(&Vector3f::new(0.0, 0.0, -1.0)));
+  assert!(f.contains_point(&Vector3f::new(0.9, -0.9, -1.0)));
+  // behind the near plane, behind the camera, beyond the far plane and outside a side
+  assert!(!f.contains_point(&Vector3f::new(0.0, 0.0, -0.05)));
+  assert!(!f.contains_point(&Vector3f::new(0.0, 0.0, 1.0)));
+  assert!(!f.contains_point(&Vector3f::new(0.0, 0.0, -101.0)));
+  assert!(!f.contains_point(&Vector3f::new(1.1, 0.0, -1.0)));
+
+  assert!(f.intersects_sphere(&Vector3f::new(1.2, 0.0, -1.0), 0.5));
+  assert!(!f.intersects_sphere(&Vector3f::new(0.0, 0.0, 1.0), 0.5));
+  assert!(f.intersects_aabb(&Vector3f::new(-0.5, -0.5, -2.0), &Vector3f::new(0.5, 0.5, -1.0)));
+  assert!(!f.intersects_aabb(&Vector3f::new(2.0, -0.5, -1.5), &Vector3f::new(3.0, 0.5, -1.0)));
+}
+
+#[test]
+fn stereo_frustum_contains_both_eyes() {
+  let _guard = SDK.lock();
+  let sdk = Ovr::initialize().unwrap();
+  let hmd = sdk.create_hmd_debug(HmdDK2).unwrap();
+  let fov = hmd.get_desc().DefaultEyeFov;
+  let desc = [hmd.get_render_desc(EyeL, fov[0].clone()), hmd.get_render_desc(EyeR, fov[1].clone())];
+  let (znear, zfar) = (0.1, 100.0);
+  let stereo = frustum::stereo_frustum(&desc, znear, zfar, true);
+
+  for d in desc.iter() {
+    // the corners of the eye's frustum, the eye looks down -Z
+    let eye = d.HmdToEyeViewOffset.neg();
+    for &z in [znear, zfar].iter() {
+      for &x in [-d.Fov.LeftTan * z, d.Fov.RightTan * z].iter() {
+        for &y in [-d.Fov.DownTan * z, d.Fov.UpTan * z].iter() {
+          let corner = eye.add(&Vector3f::new(x, y, -z));
+          assert!(contains(&stereo, &corner), "{} is outside of the stereo frustum", corner);
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,121 @@
+//! Feeds synthetic sensor readings with a known bias, gravity and hard iron
+//! offset to the IMU estimators.
+
+extern crate "libovr-rs" as ovr;
+
+use std::default::Default;
+use std::num::Float;
+use std::f32::consts::PI;
+
+use ovr::imu::{GyroBiasEstimator, GravityEstimator, HardIronEstimator, RadiansPerSecond, MetersPerSecondSquared, Gauss};
+use ovr::ffi::{SensorData, Vector3f};
+
+/// Alternates between `+amplitude` and `-amplitude`, a deterministic zero
+/// mean noise.
+fn noise(i: uint, amplitude: f32) -> f32 {
+  if i % 2 == 0 { amplitude } else { -amplitude }
+}
+
+/// A reading at 100 Hz.
+fn reading(i: uint, accelerometer: Vector3f, gyro: Vector3f, magnetometer: Vector3f) -> SensorData {
+  let mut data: SensorData = Default::default();
+  data.Accelerometer = accelerometer;
+  data.Gyro = gyro;
+  data.Magnetometer = magnetometer;
+  data.Temperature = 30.0;
+  data.TimeInSeconds = i as f32 * 0.01;
+  data
+}
+
+fn assert_close(actual: &Vector3f, expected: &Vector3f, tolerance: f32) {
+  assert!(actual.sub(expected).length() < tolerance, "{} is not within {} of {}", actual, tolerance, expected);
+}
+
+#[test]
+fn gyro_bias_converges_while_still() {
+  let bias = Vector3f::new(0.01, -0.02, 0.005);
+  let still = |i: uint| {
+    reading(i, Vector3f::new(0.0, 9.8, noise(i, 0.05)),
+            bias.add(&Vector3f::new(noise(i, 0.002), noise(i + 1, 0.002), 0.0)),
+            Vector3f::new(0.0, 0.0, 0.0))
+  };
+
+  let mut estimator = GyroBiasEstimator::new(1.0);
+  for i in range(0u, 100) {
+    estimator.add(&still(i));
+    assert!(estimator.bias().is_none());
+  }
+  for i in range(100u, 151) {
+    estimator.add(&still(i));
+  }
+  assert!((estimator.stationary_time() - 1.5).abs() < 1e-4);
+  match estimator.bias() {
+    Some(RadiansPerSecond(estimate)) => assert_close(&estimate, &bias, 1e-4),
+    None => panic!("no bias after {} s", estimator.stationary_time()),
+  }
+
+  // turning the headset starts a new window
+  estimator.add(&reading(151, Vector3f::new(0.0, 9.8, 0.0), Vector3f::new(0.0, 1.0, 0.0), Vector3f::new(0.0, 0.0, 0.0)));
+  assert!(estimator.bias().is_none());
+  assert_eq!(estimator.stationary_time(), 0.0);
+}
+
+#[test]
+fn gravity_converges_to_the_tilted_up_vector() {
+  // the headset is pitched by 0.3 radians
+  let tilt = 0.3f32;
+  let gravity = Vector3f::new(0.0, 9.8 * tilt.cos(), 9.8 * tilt.sin());
+
+  let mut estimator = GravityEstimator::new(0.1);
+  assert!(estimator.gravity().is_none());
+  // start far off, then settle over 20 time constants
+  estimator.add(&reading(0, Vector3f::new(3.0, 8.0, -2.0), Vector3f::new(0.0, 0.0, 0.0), Vector3f::new(0.0, 0.0, 0.0)));
+  for i in range(1u, 201) {
+    let accelerometer = gravity.add(&Vector3f::new(noise(i, 0.05), noise(i + 1, 0.05), noise(i, 0.05)));
+    estimator.add(&reading(i, accelerometer, Vector3f::new(0.0, 0.0, 0.0), Vector3f::new(0.0, 0.0, 0.0)));
+  }
+
+  match estimator.gravity() {
+    Some(MetersPerSecondSquared(estimate)) => assert_close(&estimate, &gravity, 0.01),
+    None => panic!("no gravity estimate"),
+  }
+  assert_close(&estimator.up().unwrap(), &gravity.normalized(), 1e-3);
+  assert!((estimator.tilt().unwrap() - tilt).abs() < 1e-3);
+
+  estimator.reset();
+  assert!(estimator.gravity().is_none());
+}
+
+#[test]
+fn hard_iron_offset_is_the_center_of_a_full_sweep() {
+  let offset = Vector3f::new(0.1, -0.2, 0.3);
+  let strength = 0.5f32;
+  let mut estimator = HardIronEstimator::new();
+  assert!(estimator.offset().is_none());
+
+  // the field seen from directions 15 degrees apart, which includes both
+  // directions of every axis
+  let step = PI / 12.0;
+  let mut i = 0u;
+  for polar in range(0u, 13) {
+    for azimuth in range(0u, 24) {
+      let (theta, phi) = (polar as f32 * step, azimuth as f32 * step);
+      let field = Vector3f::new(theta.sin() * phi.cos(), theta.sin() * phi.sin(), theta.cos()).scale(strength);
+      estimator.add(&reading(i, Vector3f::new(0.0, 9.8, 0.0), Vector3f::new(0.0, 0.0, 0.0), field.add(&offset)));
+      i += 1;
+    }
+    // from 90 degrees on, every axis has seen `min_range`
+    assert_eq!(estimator.offset().is_some(), polar >= 6);
+  }
+
+  assert_close(&estimator.range(), &Vector3f::new(2.0 * strength, 2.0 * strength, 2.0 * strength), 1e-5);
+  match estimator.offset() {
+    Some(Gauss(estimate)) => assert_close(&estimate, &offset, 1e-5),
+    None => panic!("no offset for a range of {}", estimator.range()),
+  }
+  assert!((estimator.field_strength().unwrap() - strength).abs() < 1e-5);
+
+  estimator.reset();
+  assert!(estimator.offset().is_none());
+  assert_eq!(estimator.min_range, 0.4);
+}
@@ -0,0 +1,63 @@
+//! Parses latency tester results and drives `LatencyTester` with a scripted
+//! mock HMD.
+
+extern crate "libovr-rs" as ovr;
+
+use ovr::HmdDK1;
+use ovr::mock::{MockHmd, Still};
+use ovr::latency::{LatencyResult, LatencyTester, LatencyIdle, LatencyTesting, LatencyFinished, LatencyUnparsed};
+
+#[test]
+fn parses_result_form() {
+  let text = "RESULT=41.2 (add half Tracker period) [min=39.8 max=43.5]";
+  assert_eq!(LatencyResult::parse(text),
+             Some(LatencyResult{ average: 41.2, min: 39.8, max: 43.5, text: text.to_string() }));
+}
+
+#[test]
+fn parses_average_form() {
+  let text = "Average=38.0 min=35.5 max=40.25";
+  assert_eq!(LatencyResult::parse(text),
+             Some(LatencyResult{ average: 38.0, min: 35.5, max: 40.25, text: text.to_string() }));
+}
+
+#[test]
+fn rejects_malformed_results() {
+  assert_eq!(LatencyResult::parse("RESULT=41.2 [min=39.8]"), None);
+  assert_eq!(LatencyResult::parse("RESULT=abc [min=39.8 max=43.5]"), None);
+  assert_eq!(LatencyResult::parse(""), None);
+
+  let hmd = MockHmd::new(HmdDK1, Still);
+  hmd.script_latency_test(vec![], "timed out");
+  let mut tester = LatencyTester::new();
+  tester.update(&hmd);
+  assert_eq!(tester.state(), &LatencyUnparsed("timed out".to_string()));
+  assert!(tester.results().is_empty());
+}
+
+#[test]
+fn runs_idle_testing_finished() {
+  let hmd = MockHmd::new(HmdDK1, Still);
+  let mut tester = LatencyTester::new();
+  tester.update(&hmd);
+  assert_eq!(tester.state(), &LatencyIdle);
+
+  let text = "RESULT=41.2 (add half Tracker period) [min=39.8 max=43.5]";
+  hmd.script_latency_test(vec![[255, 255, 255], [0, 0, 0]], text);
+  let frame = tester.update(&hmd);
+  assert_eq!(frame.clear_color, Some([255, 255, 255]));
+  assert_eq!(tester.state(), &LatencyTesting);
+  let frame = tester.update(&hmd);
+  assert_eq!(frame.clear_color, Some([0, 0, 0]));
+  assert_eq!(tester.state(), &LatencyTesting);
+
+  let frame = tester.update(&hmd);
+  assert_eq!(frame.clear_color, None);
+  let result = LatencyResult{ average: 41.2, min: 39.8, max: 43.5, text: text.to_string() };
+  assert_eq!(tester.state(), &LatencyFinished(result.clone()));
+  assert_eq!(tester.results(), [result.clone()].as_slice());
+
+  // the result is reported once and stays until the next test
+  tester.update(&hmd);
+  assert_eq!(tester.state(), &LatencyFinished(result));
+}
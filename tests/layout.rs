@@ -0,0 +1,180 @@
+//! Compares size, alignment and field offsets of the `#[repr(C)]` structs in
+//! `ffi` against the C compiler's view of `OVR_CAPI.h` and `OVR_CAPI_GL.h`,
+//! as reported by the shim in `tests/ovr_layout.cpp`. The shim is compiled
+//! with the `layout-tests` feature: `cargo test --features layout-tests`.
+
+#![feature(macro_rules)]
+#![allow(non_snake_case)]
+
+extern crate libc;
+extern crate "libovr-rs" as ovr;
+
+#[cfg(all(feature = "layout-tests", target_os = "linux", target_arch = "x86_64"))]
+mod linux_x86_64 {
+
+  use libc::{c_char, size_t};
+  use std::mem;
+  use std::c_str::ToCStr;
+  use ovr::ffi;
+
+  #[link(name = "ovr_layout", kind = "static")]
+  extern {
+    fn ovr_layout_query(type_name: *const c_char, field: *const c_char) -> size_t;
+  }
+
+  fn query(c_type: &str, field: &str) -> uint {
+    let value = c_type.with_c_str(|t| field.with_c_str(|f| unsafe { ovr_layout_query(t, f) }));
+    assert!(value != !0, "{}.{} is missing in tests/ovr_layout.cpp", c_type, field);
+    value as uint
+  }
+
+  /// Checks a Rust struct against a C struct. Each field is given as the Rust
+  /// field name followed by the C field name.
+  macro_rules! check_layout(
+    ($rust:ident, $c:expr, [$($field:ident => $c_field:expr),*]) => ({
+      assert_eq!(mem::size_of::<ffi::$rust>(), query($c, "sizeof"));
+      assert_eq!(mem::min_align_of::<ffi::$rust>(), query($c, "alignof"));
+      let value: ffi::$rust = unsafe { mem::zeroed() };
+      let base = &value as *const ffi::$rust as uint;
+      $(
+        let offset = &value.$field as *const _ as uint - base;
+        assert!(offset == query($c, $c_field),
+                "offset of {}.{} is {}, expected {}",
+                stringify!($rust), stringify!($field), offset, query($c, $c_field));
+      )*
+      unsafe { mem::forget(value) };
+    })
+  )
+
+  #[test]
+  fn math_types() {
+    check_layout!(Vector2i, "ovrVector2i", [x => "x", y => "y"]);
+    check_layout!(Sizei, "ovrSizei", [x => "w", y => "h"]);
+    check_layout!(Recti, "ovrRecti", [pos => "Pos", size => "Size"]);
+    check_layout!(Quatf, "ovrQuatf", [x => "x", y => "y", z => "z", w => "w"]);
+    check_layout!(Vector2f, "ovrVector2f", [x => "x", y => "y"]);
+    check_layout!(Vector3f, "ovrVector3f", [x => "x", y => "y", z => "z"]);
+    check_layout!(Matrix4f, "ovrMatrix4f", [M => "M"]);
+    check_layout!(Posef, "ovrPosef", [Orientation => "Orientation", Position => "Position"]);
+    check_layout!(PoseStatef, "ovrPoseStatef", [
+      ThePose             => "ThePose",
+      AngularVelocity     => "AngularVelocity",
+      LinearVelocity      => "LinearVelocity",
+      AngularAcceleration => "AngularAcceleration",
+      LinearAcceleration  => "LinearAcceleration",
+      TimeInSeconds       => "TimeInSeconds"
+    ]);
+    check_layout!(FovPort, "ovrFovPort", [
+      UpTan    => "UpTan",
+      DownTan  => "DownTan",
+      LeftTan  => "LeftTan",
+      RightTan => "RightTan"
+    ]);
+  }
+
+  #[test]
+  fn hmd_desc() {
+    check_layout!(HmdDesc, "ovrHmdDesc", [
+      Handle                     => "Handle",
+      Type                       => "Type",
+      ProductName                => "ProductName",
+      Manufacturer               => "Manufacturer",
+      VendorId                   => "VendorId",
+      ProductId                  => "ProductId",
+      SerialNumber               => "SerialNumber",
+      FirmwareMajor              => "FirmwareMajor",
+      FirmwareMinor              => "FirmwareMinor",
+      CameraFrustumHFovInRadians => "CameraFrustumHFovInRadians",
+      CameraFrustumVFovInRadians => "CameraFrustumVFovInRadians",
+      CameraFrustumNearZInMeters => "CameraFrustumNearZInMeters",
+      CameraFrustumFarZInMeters  => "CameraFrustumFarZInMeters",
+      HmdCaps                    => "HmdCaps",
+      TrackingCaps               => "TrackingCaps",
+      DistortionCaps             => "DistortionCaps",
+      DefaultEyeFov              => "DefaultEyeFov",
+      MaxEyeFov                  => "MaxEyeFov",
+      EyeRenderOrder             => "EyeRenderOrder",
+      Resolution                 => "Resolution",
+      WindowsPos                 => "WindowsPos",
+      DisplayDeviceName          => "DisplayDeviceName",
+      DisplayId                  => "DisplayId"
+    ]);
+  }
+
+  #[test]
+  fn tracking_types() {
+    check_layout!(SensorData, "ovrSensorData", [
+      Accelerometer => "Accelerometer",
+      Gyro          => "Gyro",
+      Magnetometer  => "Magnetometer",
+      Temperature   => "Temperature",
+      TimeInSeconds => "TimeInSeconds"
+    ]);
+    check_layout!(TrackingState, "ovrTrackingState", [
+      HeadPose                 => "HeadPose",
+      CameraPose               => "CameraPose",
+      LeveledCameraPose        => "LeveledCameraPose",
+      RawSensorData            => "RawSensorData",
+      StatusFlags              => "StatusFlags",
+      LastVisionProcessingTime => "LastVisionProcessingTime",
+      LastVisionFrameLatency   => "LastVisionFrameLatency",
+      LastCameraFrameCounter   => "LastCameraFrameCounter"
+    ]);
+  }
+
+  #[test]
+  fn rendering_types() {
+    check_layout!(FrameTiming, "ovrFrameTiming", [
+      DeltaSeconds           => "DeltaSeconds",
+      ThisFrameSeconds       => "ThisFrameSeconds",
+      TimewarpPointSeconds   => "TimewarpPointSeconds",
+      NextFrameSeconds       => "NextFrameSeconds",
+      ScanoutMidpointSeconds => "ScanoutMidpointSeconds",
+      EyeScanoutSeconds      => "EyeScanoutSeconds"
+    ]);
+    check_layout!(EyeRenderDesc, "ovrEyeRenderDesc", [
+      Eye                       => "Eye",
+      Fov                       => "Fov",
+      DistortedViewport         => "DistortedViewport",
+      PixelsPerTanAngleAtCenter => "PixelsPerTanAngleAtCenter",
+      HmdToEyeViewOffset        => "HmdToEyeViewOffset"
+    ]);
+    check_layout!(DistortionVertex, "ovrDistortionVertex", [
+      ScreenPosNDC   => "ScreenPosNDC",
+      TimeWarpFactor => "TimeWarpFactor",
+      VignetteFactor => "VignetteFactor",
+      TanEyeAnglesR  => "TanEyeAnglesR",
+      TanEyeAnglesG  => "TanEyeAnglesG",
+      TanEyeAnglesB  => "TanEyeAnglesB"
+    ]);
+    check_layout!(DistortionMesh, "ovrDistortionMesh", [
+      pVertexData => "pVertexData",
+      pIndexData  => "pIndexData",
+      VertexCount => "VertexCount",
+      IndexCount  => "IndexCount"
+    ]);
+    check_layout!(RenderAPIConfigHeader, "ovrRenderAPIConfigHeader", [
+      API         => "API",
+      RTSize      => "RTSize",
+      Multisample => "Multisample"
+    ]);
+    check_layout!(RenderAPIConfig, "ovrRenderAPIConfig", [Header => "Header", PlatformData => "PlatformData"]);
+    check_layout!(TextureHeader, "ovrTextureHeader", [
+      API            => "API",
+      TextureSize    => "TextureSize",
+      RenderViewport => "RenderViewport"
+    ]);
+    check_layout!(Texture, "ovrTexture", [Header => "Header", PlatformData => "PlatformData"]);
+    check_layout!(HSWDisplayState, "ovrHSWDisplayState", [
+      Displayed       => "Displayed",
+      StartTime       => "StartTime",
+      DismissibleTime => "DismissibleTime"
+    ]);
+  }
+
+  #[test]
+  fn gl_types_fit_generic_types() {
+    assert!(query("ovrGLConfigData", "sizeof") <= mem::size_of::<ffi::RenderAPIConfig>());
+    assert!(query("ovrGLTextureData", "sizeof") <= mem::size_of::<ffi::Texture>());
+  }
+}
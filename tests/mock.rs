@@ -0,0 +1,92 @@
+//! Checks the device profiles, frame timing and recentering of `MockHmd`.
+
+extern crate "libovr-rs" as ovr;
+
+use std::num::Float;
+
+use ovr::{HmdType, HmdDK1, HmdDKHD, HmdDK2, HmdDevice, EyeL, EyeR};
+use ovr::mock::{MockHmd, Still, Sway};
+use ovr::ffi::{FovPort, Vector3f};
+
+fn assert_timing_spacing(hmd: &MockHmd, interval: f64) {
+  for frame in range(0i32, 4) {
+    let timing = hmd.get_frame_timing(frame);
+    let next = hmd.get_frame_timing(frame + 1);
+    assert!((next.ThisFrameSeconds - timing.ThisFrameSeconds - interval).abs() < 1e-9);
+    assert!((timing.NextFrameSeconds - timing.ThisFrameSeconds - interval).abs() < 1e-9);
+    assert!(timing.TimewarpPointSeconds < timing.NextFrameSeconds);
+    assert!(timing.ScanoutMidpointSeconds > timing.NextFrameSeconds);
+  }
+}
+
+fn check_profile(hmd_type: HmdType, width: i32, height: i32, left_fov: FovPort, refresh_rate: f64) {
+  let hmd = MockHmd::new(hmd_type, Still);
+  let desc = hmd.get_desc();
+  assert_eq!((desc.Resolution.x, desc.Resolution.y), (width, height));
+
+  let (l, r) = (&desc.DefaultEyeFov[0], &desc.DefaultEyeFov[1]);
+  assert_eq!((l.UpTan, l.DownTan, l.LeftTan, l.RightTan),
+             (left_fov.UpTan, left_fov.DownTan, left_fov.LeftTan, left_fov.RightTan));
+  // the right eye is mirrored
+  assert_eq!((r.UpTan, r.DownTan, r.LeftTan, r.RightTan),
+             (left_fov.UpTan, left_fov.DownTan, left_fov.RightTan, left_fov.LeftTan));
+
+  assert_eq!(hmd.frame_interval(), 1.0 / refresh_rate);
+  assert_timing_spacing(&hmd, 1.0 / refresh_rate);
+}
+
+#[test]
+fn dk1_profile() {
+  check_profile(HmdDK1, 1280, 800, FovPort{ UpTan: 1.2348, DownTan: 1.2348, LeftTan: 1.0535, RightTan: 1.2348 }, 60.0);
+}
+
+#[test]
+fn dkhd_profile() {
+  check_profile(HmdDKHD, 1920, 1080, FovPort{ UpTan: 1.1147, DownTan: 1.1147, LeftTan: 1.0535, RightTan: 1.2348 }, 60.0);
+}
+
+#[test]
+fn dk2_profile() {
+  check_profile(HmdDK2, 1920, 1080, FovPort{ UpTan: 1.3292, DownTan: 1.3292, LeftTan: 1.0586, RightTan: 1.0923 }, 75.0);
+}
+
+#[test]
+fn eye_poses_are_predicted_for_the_given_frame() {
+  let hmd = MockHmd::new(HmdDK2, Still);
+  hmd.begin_frame(2);
+  let offsets = [Vector3f::new(0.032, 0.0, 0.0), Vector3f::new(-0.032, 0.0, 0.0)];
+  let (_, state) = hmd.get_eye_poses(7, offsets);
+  assert_eq!(state.HeadPose.TimeInSeconds, hmd.get_frame_timing(7).ScanoutMidpointSeconds);
+}
+
+#[test]
+fn begin_frame_follows_the_frame_timing() {
+  let hmd = MockHmd::new(HmdDK2, Still);
+  let timing = hmd.begin_frame(3);
+  assert_eq!(timing.ThisFrameSeconds, hmd.get_frame_timing(3).ThisFrameSeconds);
+  hmd.end_frame_timing();
+  let pose = hmd.get_hmd_pose_per_eye(EyeL);
+  // a still head gives identity timewarp matrices
+  for m in hmd.get_eye_timewarp_matrices(EyeR, pose).iter() {
+    for i in range(0u, 4) {
+      for j in range(0u, 4) {
+        let expected = if i == j { 1.0 } else { 0.0 };
+        assert!((m.M[i][j] - expected).abs() < 1e-6);
+      }
+    }
+  }
+}
+
+#[test]
+fn recenter_zeroes_yaw_and_position() {
+  // a quarter period in, the head is turned and moved the furthest
+  let hmd = MockHmd::new(HmdDK2, Sway{ yaw_amplitude: 0.5, sway_amplitude: 0.1, period: 2.0 });
+  let before = hmd.get_tracking_state(0.5).HeadPose.ThePose;
+  assert!((before.Orientation.yaw() - 0.5).abs() < 1e-5);
+  assert!((before.Position.x - 0.1).abs() < 1e-5);
+
+  hmd.recenter_pose();
+  let after = hmd.get_tracking_state(0.5).HeadPose.ThePose;
+  assert!(after.Orientation.yaw().abs() < 1e-5);
+  assert!(after.Position.length() < 1e-5);
+}
@@ -0,0 +1,54 @@
+//! Round trips through the `nalgebra` conversions, run with
+//! `cargo test --features nalgebra`.
+
+#![cfg(feature = "nalgebra")]
+
+extern crate nalgebra;
+extern crate "libovr-rs" as ovr;
+
+use nalgebra::{Vec3, Quat, Mat4, Iso3};
+
+use ovr::convert::{ToMath, ToOvr};
+use ovr::ffi::{Vector3f, Quatf, Matrix4f, Posef};
+
+#[test]
+fn vector_and_quaternion_round_trip() {
+  let v = Vector3f::new(1.0, 2.0, 3.0);
+  let m: Vec3<f32> = v.to_math();
+  assert_eq!(m, Vec3::new(1.0, 2.0, 3.0));
+  let back: Vector3f = m.to_ovr();
+  assert_eq!((back.x, back.y, back.z), (1.0, 2.0, 3.0));
+
+  let q = Quatf::new(0.1, 0.2, 0.3, 0.9);
+  let m: Quat<f32> = q.to_math();
+  assert_eq!((m.w, m.i, m.j, m.k), (0.9, 0.1, 0.2, 0.3));
+  let back: Quatf = m.to_ovr();
+  assert_eq!((back.x, back.y, back.z, back.w), (0.1, 0.2, 0.3, 0.9));
+}
+
+#[test]
+fn matrix_round_trip_keeps_rows() {
+  let mut m = Matrix4f::identity();
+  for r in range(0u, 4) {
+    for c in range(0u, 4) {
+      m.M[r][c] = (r * 4 + c) as f32;
+    }
+  }
+  let math: Mat4<f32> = m.to_math();
+  assert_eq!((math.m12, math.m21, math.m34), (1.0, 4.0, 11.0));
+  let back: Matrix4f = math.to_ovr();
+  assert_eq!(back.M, m.M);
+}
+
+#[test]
+fn translation_ends_up_in_the_last_column() {
+  let pose = Posef::new(Quatf::new(0.0, 0.0, 0.0, 1.0), Vector3f::new(1.0, 2.0, 3.0));
+  let math: Mat4<f32> = Matrix4f::from_pose(&pose).to_math();
+  assert_eq!((math.m14, math.m24, math.m34, math.m44), (1.0, 2.0, 3.0, 1.0));
+
+  let iso: Iso3<f32> = pose.to_math();
+  assert_eq!(iso.translation, Vec3::new(1.0, 2.0, 3.0));
+  let back: Posef = iso.to_ovr();
+  assert_eq!((back.Position.x, back.Position.y, back.Position.z), (1.0, 2.0, 3.0));
+  assert_eq!(back.Orientation.w, 1.0);
+}
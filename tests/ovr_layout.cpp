@@ -0,0 +1,185 @@
+/*
+ * Reports the layout of the structs in OVR_CAPI.h and OVR_CAPI_GL.h as seen
+ * by the C compiler. Used by tests/layout.rs to verify the hand written
+ * #[repr(C)] structs in src/ffi.rs.
+ *
+ * Compiled as C++ because OVR_CAPI_GL.h relies on C++ struct name lookup.
+ */
+
+#include <stddef.h>
+#include <string.h>
+
+#include "Kernel/OVR_Types.h"
+#include "OVR_CAPI.h"
+#include "OVR_CAPI_GL.h"
+
+typedef struct {
+    const char* type;
+    const char* field;
+    size_t      value;
+} LayoutEntry;
+
+#define SIZE(T)          { #T, "sizeof", sizeof(T) }, { #T, "alignof", __alignof__(T) }
+#define FIELD(T, F)      { #T, #F, offsetof(T, F) }
+
+static const LayoutEntry entries[] = {
+    SIZE(ovrVector2i),
+    FIELD(ovrVector2i, x),
+    FIELD(ovrVector2i, y),
+
+    SIZE(ovrSizei),
+    FIELD(ovrSizei, w),
+    FIELD(ovrSizei, h),
+
+    SIZE(ovrRecti),
+    FIELD(ovrRecti, Pos),
+    FIELD(ovrRecti, Size),
+
+    SIZE(ovrQuatf),
+    FIELD(ovrQuatf, x),
+    FIELD(ovrQuatf, y),
+    FIELD(ovrQuatf, z),
+    FIELD(ovrQuatf, w),
+
+    SIZE(ovrVector2f),
+    FIELD(ovrVector2f, x),
+    FIELD(ovrVector2f, y),
+
+    SIZE(ovrVector3f),
+    FIELD(ovrVector3f, x),
+    FIELD(ovrVector3f, y),
+    FIELD(ovrVector3f, z),
+
+    SIZE(ovrMatrix4f),
+    FIELD(ovrMatrix4f, M),
+
+    SIZE(ovrPosef),
+    FIELD(ovrPosef, Orientation),
+    FIELD(ovrPosef, Position),
+
+    SIZE(ovrPoseStatef),
+    FIELD(ovrPoseStatef, ThePose),
+    FIELD(ovrPoseStatef, AngularVelocity),
+    FIELD(ovrPoseStatef, LinearVelocity),
+    FIELD(ovrPoseStatef, AngularAcceleration),
+    FIELD(ovrPoseStatef, LinearAcceleration),
+    FIELD(ovrPoseStatef, TimeInSeconds),
+
+    SIZE(ovrFovPort),
+    FIELD(ovrFovPort, UpTan),
+    FIELD(ovrFovPort, DownTan),
+    FIELD(ovrFovPort, LeftTan),
+    FIELD(ovrFovPort, RightTan),
+
+    SIZE(ovrHmdDesc),
+    FIELD(ovrHmdDesc, Handle),
+    FIELD(ovrHmdDesc, Type),
+    FIELD(ovrHmdDesc, ProductName),
+    FIELD(ovrHmdDesc, Manufacturer),
+    FIELD(ovrHmdDesc, VendorId),
+    FIELD(ovrHmdDesc, ProductId),
+    FIELD(ovrHmdDesc, SerialNumber),
+    FIELD(ovrHmdDesc, FirmwareMajor),
+    FIELD(ovrHmdDesc, FirmwareMinor),
+    FIELD(ovrHmdDesc, CameraFrustumHFovInRadians),
+    FIELD(ovrHmdDesc, CameraFrustumVFovInRadians),
+    FIELD(ovrHmdDesc, CameraFrustumNearZInMeters),
+    FIELD(ovrHmdDesc, CameraFrustumFarZInMeters),
+    FIELD(ovrHmdDesc, HmdCaps),
+    FIELD(ovrHmdDesc, TrackingCaps),
+    FIELD(ovrHmdDesc, DistortionCaps),
+    FIELD(ovrHmdDesc, DefaultEyeFov),
+    FIELD(ovrHmdDesc, MaxEyeFov),
+    FIELD(ovrHmdDesc, EyeRenderOrder),
+    FIELD(ovrHmdDesc, Resolution),
+    FIELD(ovrHmdDesc, WindowsPos),
+    FIELD(ovrHmdDesc, DisplayDeviceName),
+    FIELD(ovrHmdDesc, DisplayId),
+
+    SIZE(ovrSensorData),
+    FIELD(ovrSensorData, Accelerometer),
+    FIELD(ovrSensorData, Gyro),
+    FIELD(ovrSensorData, Magnetometer),
+    FIELD(ovrSensorData, Temperature),
+    FIELD(ovrSensorData, TimeInSeconds),
+
+    SIZE(ovrTrackingState),
+    FIELD(ovrTrackingState, HeadPose),
+    FIELD(ovrTrackingState, CameraPose),
+    FIELD(ovrTrackingState, LeveledCameraPose),
+    FIELD(ovrTrackingState, RawSensorData),
+    FIELD(ovrTrackingState, StatusFlags),
+    FIELD(ovrTrackingState, LastVisionProcessingTime),
+    FIELD(ovrTrackingState, LastVisionFrameLatency),
+    FIELD(ovrTrackingState, LastCameraFrameCounter),
+
+    SIZE(ovrFrameTiming),
+    FIELD(ovrFrameTiming, DeltaSeconds),
+    FIELD(ovrFrameTiming, ThisFrameSeconds),
+    FIELD(ovrFrameTiming, TimewarpPointSeconds),
+    FIELD(ovrFrameTiming, NextFrameSeconds),
+    FIELD(ovrFrameTiming, ScanoutMidpointSeconds),
+    FIELD(ovrFrameTiming, EyeScanoutSeconds),
+
+    SIZE(ovrEyeRenderDesc),
+    FIELD(ovrEyeRenderDesc, Eye),
+    FIELD(ovrEyeRenderDesc, Fov),
+    FIELD(ovrEyeRenderDesc, DistortedViewport),
+    FIELD(ovrEyeRenderDesc, PixelsPerTanAngleAtCenter),
+    FIELD(ovrEyeRenderDesc, HmdToEyeViewOffset),
+
+    SIZE(ovrDistortionVertex),
+    FIELD(ovrDistortionVertex, ScreenPosNDC),
+    FIELD(ovrDistortionVertex, TimeWarpFactor),
+    FIELD(ovrDistortionVertex, VignetteFactor),
+    FIELD(ovrDistortionVertex, TanEyeAnglesR),
+    FIELD(ovrDistortionVertex, TanEyeAnglesG),
+    FIELD(ovrDistortionVertex, TanEyeAnglesB),
+
+    SIZE(ovrDistortionMesh),
+    FIELD(ovrDistortionMesh, pVertexData),
+    FIELD(ovrDistortionMesh, pIndexData),
+    FIELD(ovrDistortionMesh, VertexCount),
+    FIELD(ovrDistortionMesh, IndexCount),
+
+    SIZE(ovrRenderAPIConfigHeader),
+    FIELD(ovrRenderAPIConfigHeader, API),
+    FIELD(ovrRenderAPIConfigHeader, RTSize),
+    FIELD(ovrRenderAPIConfigHeader, Multisample),
+
+    SIZE(ovrRenderAPIConfig),
+    FIELD(ovrRenderAPIConfig, Header),
+    FIELD(ovrRenderAPIConfig, PlatformData),
+
+    SIZE(ovrTextureHeader),
+    FIELD(ovrTextureHeader, API),
+    FIELD(ovrTextureHeader, TextureSize),
+    FIELD(ovrTextureHeader, RenderViewport),
+
+    SIZE(ovrTexture),
+    FIELD(ovrTexture, Header),
+    FIELD(ovrTexture, PlatformData),
+
+    SIZE(ovrHSWDisplayState),
+    FIELD(ovrHSWDisplayState, Displayed),
+    FIELD(ovrHSWDisplayState, StartTime),
+    FIELD(ovrHSWDisplayState, DismissibleTime),
+
+    /* The GL specific types must fit into the generic ones they are passed as. */
+    SIZE(ovrGLConfigData),
+    SIZE(ovrGLTextureData),
+    FIELD(ovrGLTextureData, TexId),
+};
+
+/* Returns the size, alignment ("sizeof", "alignof") or field offset of a type,
+ * or (size_t)-1 if the entry is unknown. */
+extern "C" size_t ovr_layout_query(const char* type, const char* field)
+{
+    size_t i;
+    for (i = 0; i < sizeof(entries) / sizeof(entries[0]); ++i)
+    {
+        if (strcmp(entries[i].type, type) == 0 && strcmp(entries[i].field, field) == 0)
+            return entries[i].value;
+    }
+    return (size_t)-1;
+}
@@ -0,0 +1,98 @@
+//! Extrapolates hand-built pose states with both prediction modes.
+
+extern crate "libovr-rs" as ovr;
+
+use std::default::Default;
+use std::num::Float;
+
+use ovr::prediction::{mod, PredictionOptions, ConstantVelocity, ConstantAcceleration};
+use ovr::clock::SimulatedClock;
+use ovr::ffi::{PoseStatef, Posef, Quatf, Vector3f};
+
+/// A head at 10 seconds, turned by 0.3 radians, turning left at 2 radians
+/// per second and moving right at 1 meter per second.
+fn turning() -> PoseStatef {
+  let mut state: PoseStatef = Default::default();
+  state.ThePose = Posef::new(Quatf::from_axis_angle(&Vector3f::new(0.0, 1.0, 0.0), 0.3), Vector3f::new(0.0, 0.0, 0.0));
+  state.AngularVelocity = Vector3f::new(0.0, 2.0, 0.0);
+  state.LinearVelocity = Vector3f::new(1.0, 0.0, 0.0);
+  state.TimeInSeconds = 10.0;
+  state
+}
+
+fn options(mode: prediction::PredictionMode) -> PredictionOptions {
+  PredictionOptions{ mode: mode, ..PredictionOptions::new() }
+}
+
+fn assert_close(actual: f32, expected: f32, what: &str) {
+  assert!((actual - expected).abs() < 1e-5, "{} is {}, expected {}", what, actual, expected);
+}
+
+#[test]
+fn constant_velocity_turns_by_the_angular_velocity() {
+  let mut state = turning();
+  // ignored without ConstantAcceleration
+  state.AngularAcceleration = Vector3f::new(0.0, 10.0, 0.0);
+  state.LinearAcceleration = Vector3f::new(10.0, 0.0, 0.0);
+
+  let predicted = prediction::predict_with(&state, 0.05, &options(ConstantVelocity));
+  assert_close(predicted.ThePose.Orientation.yaw(), 0.3 + 2.0 * 0.05, "yaw");
+  assert_close(predicted.ThePose.Position.x, 0.05, "x");
+  assert_close(predicted.AngularVelocity.y, 2.0, "angular velocity");
+  assert_close(predicted.LinearVelocity.x, 1.0, "linear velocity");
+  assert!((predicted.TimeInSeconds - 10.05).abs() < 1e-9);
+}
+
+#[test]
+fn constant_acceleration_updates_the_velocities() {
+  let mut state: PoseStatef = Default::default();
+  state.ThePose = Posef::identity();
+  state.AngularVelocity = Vector3f::new(0.0, 1.0, 0.0);
+  state.AngularAcceleration = Vector3f::new(0.0, 2.0, 0.0);
+  state.LinearAcceleration = Vector3f::new(0.0, 0.0, -4.0);
+
+  let dt = 0.05f32;
+  let predicted = prediction::predict_with(&state, dt as f64, &options(ConstantAcceleration));
+  assert_close(predicted.ThePose.Orientation.yaw(), dt + 0.5 * 2.0 * dt * dt, "yaw");
+  assert_close(predicted.ThePose.Position.z, -0.5 * 4.0 * dt * dt, "z");
+  assert_close(predicted.AngularVelocity.y, 1.0 + 2.0 * dt, "angular velocity");
+  assert_close(predicted.LinearVelocity.z, -4.0 * dt, "linear velocity");
+  // the accelerations stay constant
+  assert_close(predicted.AngularAcceleration.y, 2.0, "angular acceleration");
+  assert_close(predicted.LinearAcceleration.z, -4.0, "linear acceleration");
+}
+
+#[test]
+fn interval_is_clamped_to_max_interval() {
+  let state = turning();
+  let options = options(ConstantVelocity);
+  assert_eq!(options.max_interval, prediction::DEFAULT_MAX_INTERVAL);
+
+  let ahead = prediction::predict_with(&state, 1.0, &options);
+  assert_eq!(ahead.TimeInSeconds, 10.0 + options.max_interval);
+  assert_close(ahead.ThePose.Orientation.yaw(), 0.3 + 2.0 * options.max_interval as f32, "yaw ahead");
+
+  let behind = prediction::predict_with(&state, -1.0, &options);
+  assert_eq!(behind.TimeInSeconds, 10.0 - options.max_interval);
+  assert_close(behind.ThePose.Orientation.yaw(), 0.3 - 2.0 * options.max_interval as f32, "yaw behind");
+
+  let narrow = PredictionOptions{ max_interval: 0.01, ..options };
+  assert_close(prediction::predict_with(&state, 0.05, &narrow).ThePose.Position.x, 0.01, "x");
+}
+
+#[test]
+fn predict_to_now_uses_the_clock() {
+  let state = turning();
+  let clock = SimulatedClock::new(10.0);
+  let options = options(ConstantVelocity);
+
+  let now = prediction::predict_to_now(&state, &clock, &options);
+  assert_eq!(now.TimeInSeconds, 10.0);
+  assert_close(now.ThePose.Orientation.yaw(), 0.3, "yaw");
+
+  clock.advance(0.025);
+  let later = prediction::predict_to_now(&state, &clock, &options);
+  assert!((later.TimeInSeconds - 10.025).abs() < 1e-9);
+  assert_close(later.ThePose.Orientation.yaw(), 0.3 + 2.0 * 0.025, "yaw");
+  assert_close(later.ThePose.Position.x, 0.025, "x");
+}
@@ -0,0 +1,108 @@
+//! Writes a recording of a mock DK2 to memory and reads it back.
+
+extern crate "libovr-rs" as ovr;
+
+use std::io::{mod, BufReader, MemWriter};
+use std::f64;
+
+use ovr::{HmdDK2, HmdDevice};
+use ovr::mock::{MockHmd, Sway};
+use ovr::recording::{mod, Recorder, RecordingReader, RecordingHeader};
+use ovr::clock::SimulatedClock;
+use ovr::ffi::{TrackingState, Vector3f, Posef};
+
+fn header(hmd: &MockHmd) -> RecordingHeader {
+  RecordingHeader::from_desc(hmd.get_desc(), "0.4.4", 100.0)
+}
+
+fn vector(v: &Vector3f) -> (f32, f32, f32) {
+  (v.x, v.y, v.z)
+}
+
+fn pose(p: &Posef) -> (f32, f32, f32, f32, (f32, f32, f32)) {
+  let q = &p.Orientation;
+  (q.x, q.y, q.z, q.w, vector(&p.Position))
+}
+
+fn assert_same_state(a: &TrackingState, b: &TrackingState) {
+  let (ha, hb) = (&a.HeadPose, &b.HeadPose);
+  assert_eq!(pose(&ha.ThePose), pose(&hb.ThePose));
+  assert_eq!(vector(&ha.AngularVelocity), vector(&hb.AngularVelocity));
+  assert_eq!(vector(&ha.LinearVelocity), vector(&hb.LinearVelocity));
+  assert_eq!(vector(&ha.AngularAcceleration), vector(&hb.AngularAcceleration));
+  assert_eq!(vector(&ha.LinearAcceleration), vector(&hb.LinearAcceleration));
+  assert_eq!(ha.TimeInSeconds, hb.TimeInSeconds);
+  assert_eq!(pose(&a.CameraPose), pose(&b.CameraPose));
+  assert_eq!(pose(&a.LeveledCameraPose), pose(&b.LeveledCameraPose));
+  let (ra, rb) = (&a.RawSensorData, &b.RawSensorData);
+  assert_eq!(vector(&ra.Accelerometer), vector(&rb.Accelerometer));
+  assert_eq!(vector(&ra.Gyro), vector(&rb.Gyro));
+  assert_eq!(vector(&ra.Magnetometer), vector(&rb.Magnetometer));
+  assert_eq!((ra.Temperature, ra.TimeInSeconds), (rb.Temperature, rb.TimeInSeconds));
+  assert_eq!(a.StatusFlags, b.StatusFlags);
+  assert_eq!((a.LastVisionProcessingTime, a.LastVisionFrameLatency, a.LastCameraFrameCounter),
+             (b.LastVisionProcessingTime, b.LastVisionFrameLatency, b.LastCameraFrameCounter));
+}
+
+#[test]
+fn round_trip() {
+  let hmd = MockHmd::new(HmdDK2, Sway{ yaw_amplitude: 0.5, sway_amplitude: 0.1, period: 2.0 });
+  let states: Vec<TrackingState> = range(0u, 10).map(|i| hmd.get_tracking_state(i as f64 * 0.01)).collect();
+
+  let mut recorder = Recorder::new(MemWriter::new(), &header(&hmd)).unwrap();
+  for s in states.iter() {
+    recorder.write_sample(s).unwrap();
+  }
+  assert_eq!(recorder.samples(), 10);
+  let bytes = recorder.into_inner().get_ref().to_vec();
+
+  // magic and little endian version
+  let magic_len = recording::MAGIC.len();
+  assert_eq!(bytes.slice_to(magic_len), recording::MAGIC);
+  assert_eq!((bytes[magic_len], bytes[magic_len + 1]),
+             (recording::FORMAT_VERSION as u8, (recording::FORMAT_VERSION >> 8) as u8));
+
+  let mut reader = RecordingReader::new(BufReader::new(bytes.as_slice())).unwrap();
+  assert_eq!(reader.header(), &header(&hmd));
+  assert_eq!(reader.header().product_name.as_slice(), "Oculus Rift DK2");
+  assert_eq!(reader.header().resolution, (1920, 1080));
+  let read: Vec<TrackingState> = reader.by_ref().collect();
+  assert!(reader.error.is_none());
+  assert_eq!(read.len(), states.len());
+  for (a, b) in read.iter().zip(states.iter()) {
+    assert_same_state(a, b);
+  }
+}
+
+#[test]
+fn rejects_newer_versions_and_other_files() {
+  let hmd = MockHmd::new(HmdDK2, Sway{ yaw_amplitude: 0.5, sway_amplitude: 0.1, period: 2.0 });
+  let mut bytes = Recorder::new(MemWriter::new(), &header(&hmd)).unwrap().into_inner().get_ref().to_vec();
+  assert!(RecordingReader::new(BufReader::new(bytes.as_slice())).is_ok());
+
+  let version = recording::FORMAT_VERSION + 1;
+  let magic_len = recording::MAGIC.len();
+  bytes[magic_len] = version as u8;
+  bytes[magic_len + 1] = (version >> 8) as u8;
+  assert!(RecordingReader::new(BufReader::new(bytes.as_slice())).is_err());
+
+  bytes[0] = b'X';
+  assert!(RecordingReader::new(BufReader::new(bytes.as_slice())).is_err());
+}
+
+#[test]
+fn record_session_rejects_invalid_sample_rates() {
+  let hmd = MockHmd::new(HmdDK2, Sway{ yaw_amplitude: 0.5, sway_amplitude: 0.1, period: 2.0 });
+  let clock = SimulatedClock::new(0.0);
+  for &rate in [0.0, -100.0, f64::NAN, f64::INFINITY].iter() {
+    let mut recorder = Recorder::new(MemWriter::new(), &header(&hmd)).unwrap();
+    match recording::record_session(&hmd, &mut recorder, &clock, rate, 1.0) {
+      Err(e) => assert_eq!(e.kind, io::InvalidInput),
+      Ok(n)  => panic!("recorded {} samples at {} Hz", n, rate),
+    }
+    assert_eq!(recorder.samples(), 0);
+  }
+
+  let mut recorder = Recorder::new(MemWriter::new(), &header(&hmd)).unwrap();
+  assert_eq!(recording::record_session(&hmd, &mut recorder, &clock, 4.0, 1.0).unwrap(), 5);
+}
@@ -0,0 +1,86 @@
+//! Plays back a short recording written with `Recorder`.
+
+extern crate "libovr-rs" as ovr;
+
+use std::default::Default;
+use std::io::{BufReader, MemWriter};
+use std::num::Float;
+
+use ovr::{HmdDK2, HmdDevice};
+use ovr::mock::{MockHmd, Still};
+use ovr::recording::{Recorder, RecordingReader, RecordingHeader};
+use ovr::replay::Replay;
+use ovr::ffi::{TrackingState, Posef, Quatf, Vector3f};
+
+/// Samples at 10, 11 and 12 seconds, moving one meter along X and turning
+/// by 0.4 radians per second.
+fn replay() -> Replay {
+  let header = RecordingHeader::from_desc(MockHmd::new(HmdDK2, Still).get_desc(), "0.4.4", 1.0);
+  let mut recorder = Recorder::new(MemWriter::new(), &header).unwrap();
+  for i in range(0u, 3) {
+    let mut state: TrackingState = Default::default();
+    state.HeadPose.ThePose = Posef::new(Quatf::from_axis_angle(&Vector3f::new(0.0, 1.0, 0.0), 0.4 * i as f32),
+                                        Vector3f::new(i as f32, 0.0, 0.0));
+    state.HeadPose.TimeInSeconds = 10.0 + i as f64;
+    recorder.write_sample(&state).unwrap();
+  }
+  let bytes = recorder.into_inner().get_ref().to_vec();
+
+  let mut reader = RecordingReader::new(BufReader::new(bytes.as_slice())).unwrap();
+  let header = reader.header().clone();
+  let samples: Vec<TrackingState> = reader.by_ref().collect();
+  assert!(reader.error.is_none());
+  Replay::new(header, samples)
+}
+
+/// The X position and yaw of the head at `abs_time`.
+fn head(replay: &Replay, abs_time: f64) -> (f32, f32) {
+  let state = replay.get_tracking_state(abs_time);
+  assert_eq!(state.HeadPose.TimeInSeconds, abs_time);
+  let pose = &state.HeadPose.ThePose;
+  (pose.Position.x, pose.Orientation.yaw())
+}
+
+fn assert_head(replay: &Replay, abs_time: f64, x: f32) {
+  let (actual_x, yaw) = head(replay, abs_time);
+  assert!((actual_x - x).abs() < 1e-5, "x is {} at {}, expected {}", actual_x, abs_time, x);
+  assert!((yaw - 0.4 * x).abs() < 1e-5, "yaw is {} at {}, expected {}", yaw, abs_time, 0.4 * x);
+}
+
+#[test]
+fn interpolates_between_samples() {
+  let replay = replay();
+  assert_eq!(replay.duration(), 2.0);
+  assert_head(&replay, 0.0, 0.0);
+  assert_head(&replay, 0.25, 0.25);
+  assert_head(&replay, 1.5, 1.5);
+  // the ends are held without looping
+  assert_head(&replay, -1.0, 0.0);
+  assert_head(&replay, 5.0, 2.0);
+}
+
+#[test]
+fn loops_past_the_end() {
+  let mut replay = replay();
+  replay.set_looping(true);
+  assert_head(&replay, 2.5, 0.5);
+  assert_head(&replay, 5.25, 1.25);
+  assert!((replay.position(-0.5) - 1.5).abs() < 1e-9);
+}
+
+#[test]
+fn seek_moves_the_playback_position() {
+  let mut replay = replay();
+  replay.seek(3.0, 1.5);
+  assert_head(&replay, 3.0, 1.5);
+  assert_head(&replay, 3.25, 1.75);
+}
+
+#[test]
+fn time_scale_changes_speed_from_the_given_time() {
+  let mut replay = replay();
+  assert_head(&replay, 0.5, 0.5);
+  replay.set_time_scale(1.0, 0.5);
+  assert_head(&replay, 1.0, 1.0);
+  assert_head(&replay, 2.0, 1.5);
+}
@@ -0,0 +1,54 @@
+//! JSON round trips of the ffi value types, run with
+//! `cargo test --features serde`.
+
+#![cfg(feature = "serde")]
+
+extern crate serde;
+extern crate "libovr-rs" as ovr;
+
+use serde::json;
+
+use ovr::{HmdDK2, HmdDevice, EyeR};
+use ovr::mock::{MockHmd, Sway};
+use ovr::ffi::{TrackingState, EyeRenderDesc};
+
+#[test]
+fn tracking_state_round_trip() {
+  let hmd = MockHmd::new(HmdDK2, Sway{ yaw_amplitude: 0.5, sway_amplitude: 0.1, period: 2.0 });
+  let state = hmd.get_tracking_state(0.3);
+
+  let text = json::to_string(&state).unwrap();
+  assert!(text.as_slice().contains("\"HeadPose\""));
+  assert!(text.as_slice().contains("\"AngularVelocity\""));
+  let back: TrackingState = json::from_str(text.as_slice()).unwrap();
+
+  let (a, b) = (&state.HeadPose, &back.HeadPose);
+  let (qa, qb) = (&a.ThePose.Orientation, &b.ThePose.Orientation);
+  assert_eq!((qa.x, qa.y, qa.z, qa.w), (qb.x, qb.y, qb.z, qb.w));
+  let (pa, pb) = (&a.ThePose.Position, &b.ThePose.Position);
+  assert_eq!((pa.x, pa.y, pa.z), (pb.x, pb.y, pb.z));
+  assert_eq!((a.AngularVelocity.y, a.LinearVelocity.x, a.TimeInSeconds),
+             (b.AngularVelocity.y, b.LinearVelocity.x, b.TimeInSeconds));
+  assert_eq!(state.StatusFlags, back.StatusFlags);
+  assert_eq!(state.CameraPose.Position.z, back.CameraPose.Position.z);
+  assert_eq!(state.RawSensorData.Temperature, back.RawSensorData.Temperature);
+}
+
+#[test]
+fn eye_render_desc_round_trip() {
+  let hmd = MockHmd::new(HmdDK2, Sway{ yaw_amplitude: 0.5, sway_amplitude: 0.1, period: 2.0 });
+  let desc = hmd.get_render_desc(EyeR, hmd.get_desc().DefaultEyeFov[1].clone());
+
+  let text = json::to_string(&desc).unwrap();
+  assert!(text.as_slice().contains("\"HmdToEyeViewOffset\""));
+  let back: EyeRenderDesc = json::from_str(text.as_slice()).unwrap();
+
+  assert_eq!(desc.Eye, back.Eye);
+  assert_eq!((desc.Fov.UpTan, desc.Fov.DownTan, desc.Fov.LeftTan, desc.Fov.RightTan),
+             (back.Fov.UpTan, back.Fov.DownTan, back.Fov.LeftTan, back.Fov.RightTan));
+  let (va, vb) = (&desc.DistortedViewport, &back.DistortedViewport);
+  assert_eq!((va.pos.x, va.pos.y, va.size.x, va.size.y), (vb.pos.x, vb.pos.y, vb.size.x, vb.size.y));
+  assert_eq!((desc.PixelsPerTanAngleAtCenter.x, desc.PixelsPerTanAngleAtCenter.y),
+             (back.PixelsPerTanAngleAtCenter.x, back.PixelsPerTanAngleAtCenter.y));
+  assert_eq!(desc.HmdToEyeViewOffset.x, back.HmdToEyeViewOffset.x);
+}
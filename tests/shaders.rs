@@ -0,0 +1,70 @@
+//! Validates the distortion shaders with an offline GLSL compiler, so no GL
+//! context is needed. Uses `glslangValidator` from the PATH, or the program
+//! named by the `GLSL_VALIDATOR` environment variable. The validation is
+//! ignored by default, run it with `cargo test -- --ignored`; it fails if
+//! the validator is not installed.
+//!
+//! Only the GLSL 1.50 variants are checked: the 1.10 prefix relies on
+//! `GL_EXT_gpu_shader4`, which offline validators do not implement.
+
+extern crate "libovr-rs" as ovr;
+
+use std::io::File;
+use std::io::process::Command;
+use std::os;
+
+use ovr::shaders;
+
+fn validator() -> String {
+  os::getenv("GLSL_VALIDATOR").unwrap_or("glslangValidator".to_string())
+}
+
+#[test]
+#[ignore]
+fn distortion_shaders_compile() {
+  let validator = validator();
+  let dir = os::tmpdir();
+  let mut failures = Vec::new();
+
+  for &(name, source) in shaders::ALL_SHADERS.iter() {
+    let stage = if name.ends_with("_vs") { "vert" } else { "frag" };
+    let path = dir.join(format!("libovr-rs-{}.{}", name, stage));
+    let mut file = File::create(&path).unwrap();
+    file.write_str(shaders::GLSL3_PREFIX).unwrap();
+    file.write_str(source).unwrap();
+    drop(file);
+
+    let output = match Command::new(validator.as_slice()).arg(&path).output() {
+      Ok(output) => output,
+      Err(e) => panic!("cannot run {}: {}, set GLSL_VALIDATOR to a GLSL validator", validator, e),
+    };
+    if !output.status.success() {
+      failures.push(format!("{}:\n{}{}", name,
+                            String::from_utf8_lossy(output.output.as_slice()),
+                            String::from_utf8_lossy(output.error.as_slice())));
+    }
+  }
+
+  assert!(failures.is_empty(), "{}", failures.connect("\n"));
+}
+
+#[test]
+fn program_selection_follows_distortion_caps() {
+  let caps = |mask: u32| ovr::BitMask{ mask: mask };
+  let chroma = ovr::ffi::DistortionCap_Chromatic;
+  let timewarp = ovr::ffi::DistortionCap_TimeWarp;
+  let vignette = ovr::ffi::DistortionCap_Vignette;
+
+  let p = shaders::distortion_program(caps(vignette));
+  assert_eq!(p.vertex.source, shaders::DISTORTION_VS);
+  assert_eq!(p.fragment.source, shaders::DISTORTION_FS);
+
+  let p = shaders::distortion_program(caps(chroma | timewarp | vignette));
+  assert_eq!(p.vertex.source, shaders::DISTORTION_TIMEWARP_CHROMA_VS);
+  assert_eq!(p.fragment.source, shaders::DISTORTION_CHROMA_FS);
+  assert_eq!(p.vertex.uniforms.len(), 4);
+
+  let p = shaders::distortion_program(caps(timewarp));
+  assert_eq!(p.vertex.source, shaders::DISTORTION_TIMEWARP_VS);
+  assert_eq!(p.fragment.source, shaders::DISTORTION_FS);
+}
@@ -0,0 +1,54 @@
+//! Checks the client side timewarp matrices against hand computed ones.
+
+extern crate "libovr-rs" as ovr;
+
+use std::num::Float;
+
+use ovr::ffi::{Matrix4f, Posef, Quatf, Vector3f};
+use ovr::timewarp;
+
+fn yaw(angle: f32) -> Quatf {
+  Quatf::from_axis_angle(&Vector3f::new(0.0, 1.0, 0.0), angle)
+}
+
+fn assert_matrix_eq(a: &Matrix4f, b: &Matrix4f) {
+  for i in range(0u, 4) {
+    for j in range(0u, 4) {
+      assert!((a.M[i][j] - b.M[i][j]).abs() < 1e-5, "{} != {} at [{}][{}]", a.M[i][j], b.M[i][j], i, j);
+    }
+  }
+}
+
+#[test]
+fn equal_poses_give_identity() {
+  let q = Quatf::from_axis_angle(&Vector3f::new(1.0, 2.0, 3.0).normalized(), 0.7);
+  assert_matrix_eq(&timewarp::orientation_timewarp_matrix(&q, &q), &Matrix4f::identity());
+
+  let pose = Posef::new(q, Vector3f::new(0.1, 1.6, -0.3));
+  for m in timewarp::eye_timewarp_matrices(&pose, &pose, &pose).iter() {
+    assert_matrix_eq(m, &Matrix4f::identity());
+  }
+}
+
+#[test]
+fn yaw_delta_is_flipped_into_the_mesh_basis() {
+  let angle = 0.1f32;
+  let (s, c) = (angle.sin(), angle.cos());
+  // the rotation about +Y, with Y and Z negated on both sides
+  let expected = Matrix4f{ M: [[c,   0.0, -s,  0.0],
+                               [0.0, 1.0, 0.0, 0.0],
+                               [s,   0.0, c,   0.0],
+                               [0.0, 0.0, 0.0, 1.0]] };
+  assert_matrix_eq(&timewarp::orientation_timewarp_matrix(&yaw(0.3), &yaw(0.3 + angle)), &expected);
+}
+
+#[test]
+fn pose_delta_of_view_matrices_matches_orientation_timewarp() {
+  let a = Quatf::from_axis_angle(&Vector3f::new(0.3, 1.0, 0.2).normalized(), 0.4);
+  let b = Quatf::from_axis_angle(&Vector3f::new(-0.5, 1.0, 0.1).normalized(), 0.55);
+  let origin = Vector3f::new(0.0, 0.0, 0.0);
+  let delta = timewarp::timewarp_compute_pose_delta(&timewarp::view_from_world(&Posef::new(a.clone(), origin.clone())),
+                                                    &timewarp::view_from_world(&Posef::new(b.clone(), origin)),
+                                                    &Matrix4f::identity());
+  assert_matrix_eq(&delta, &timewarp::orientation_timewarp_matrix(&a, &b));
+}
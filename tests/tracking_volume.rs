@@ -0,0 +1,88 @@
+//! Classifies head positions against the camera frustum of a mock DK2.
+//!
+//! The mock camera sits one meter in front of the origin, at (0, 0, -1),
+//! turned around to look back along +Z, so a point at (x, y, z) is
+//! `1 + z` meters in front of it and mirrored in X.
+
+extern crate "libovr-rs" as ovr;
+
+use std::num::Float;
+
+use ovr::{HmdDK1, HmdDK2, HmdDevice};
+use ovr::mock::{MockHmd, Still};
+use ovr::frustum;
+use ovr::tracking_volume::TrackingVolume;
+use ovr::ffi::Vector3f;
+
+fn dk2_volume() -> TrackingVolume {
+  let hmd = MockHmd::new(HmdDK2, Still);
+  TrackingVolume::from_state(hmd.get_desc(), &hmd.get_tracking_state(0.0)).unwrap()
+}
+
+fn assert_closest(volume: &TrackingVolume, p: Vector3f, plane: uint, distance: f32) {
+  let (actual_plane, actual_distance) = volume.closest_plane(&p);
+  assert_eq!(actual_plane, plane);
+  assert!((actual_distance - distance).abs() < 1e-5, "distance of {} is {}, expected {}", p, actual_distance, distance);
+  assert_eq!(volume.distance_to_boundary(&p), actual_distance);
+  assert_eq!(volume.contains(&p), distance > 0.0);
+}
+
+#[test]
+fn dk2_camera_frustum() {
+  let volume = dk2_volume();
+  assert!((volume.h_tan - (1.2915f32 * 0.5).tan()).abs() < 1e-6);
+  assert!((volume.v_tan - (0.9425f32 * 0.5).tan()).abs() < 1e-6);
+  assert_eq!((volume.near_z, volume.far_z), (0.4, 2.5));
+}
+
+#[test]
+fn dk1_has_no_tracking_volume() {
+  let hmd = MockHmd::new(HmdDK1, Still);
+  assert!(TrackingVolume::from_state(hmd.get_desc(), &hmd.get_tracking_state(0.0)).is_none());
+}
+
+#[test]
+fn classifies_points_inside_and_outside() {
+  let volume = dk2_volume();
+  let inside = [
+    Vector3f::new(0.0, 0.0, 0.0),
+    Vector3f::new(0.5, 0.3, 0.0),
+    Vector3f::new(0.0, 0.0, -0.5),
+    Vector3f::new(-1.0, -0.5, 1.0),
+  ];
+  for p in inside.iter() {
+    assert!(volume.contains(p), "{} should be inside", p);
+  }
+
+  let outside = [
+    // behind the camera
+    Vector3f::new(0.0, 0.0, -1.5),
+    // closer than the near plane
+    Vector3f::new(0.0, 0.0, -0.8),
+    // beyond the far plane
+    Vector3f::new(0.0, 0.0, 2.0),
+    // beside and above the field of view
+    Vector3f::new(1.5, 0.0, 0.0),
+    Vector3f::new(-1.5, 0.0, 0.0),
+    Vector3f::new(0.0, 1.0, 0.0),
+  ];
+  for p in outside.iter() {
+    assert!(!volume.contains(p), "{} should be outside", p);
+  }
+}
+
+#[test]
+fn distance_to_the_closest_plane() {
+  let volume = dk2_volume();
+  let (h, v) = (volume.h_tan, volume.v_tan);
+
+  // the side planes are tilted, so their distance is scaled by the cosine
+  // of the half field of view
+  assert_closest(&volume, Vector3f::new(0.0, 0.2, 0.0), frustum::Plane_Top, (v - 0.2) / (1.0 + v * v).sqrt());
+  assert_closest(&volume, Vector3f::new(0.0, 0.0, -0.5), frustum::Plane_Near, 0.1);
+  assert_closest(&volume, Vector3f::new(0.0, 0.0, -0.8), frustum::Plane_Near, -0.2);
+  assert_closest(&volume, Vector3f::new(0.0, 0.0, 2.0), frustum::Plane_Far, -0.5);
+  // +X in tracking space is the left of the camera
+  assert_closest(&volume, Vector3f::new(1.5, 0.0, 0.0), frustum::Plane_Left, (h - 1.5) / (1.0 + h * h).sqrt());
+  assert_closest(&volume, Vector3f::new(-1.5, 0.0, 0.0), frustum::Plane_Right, (h - 1.5) / (1.0 + h * h).sqrt());
+}